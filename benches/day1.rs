@@ -0,0 +1,38 @@
+//! Compares the visited sets used to search for the first repeated frequency
+//! on the real day 1 input. Run with `cargo bench`.
+extern crate advent_of_code_2018;
+
+use std::fs;
+use std::time::{Duration, Instant};
+
+use advent_of_code_2018::one::{self, VisitedSet};
+
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/1.txt");
+    let input = fs::read_to_string(path).expect("Could not read inputs/1.txt");
+    let changes: Vec<i32> = one::parse_changes(&input).expect("Could not parse inputs/1.txt");
+
+    let hashed = time(|| one::first_repeat_using(&changes, VisitedSet::hashed()));
+    let bitset = time(|| one::first_repeat_using(&changes, VisitedSet::for_changes(&changes)));
+
+    println!("hashed: {:>10.3} ms per run", millis(hashed));
+    println!("bitset: {:>10.3} ms per run", millis(bitset));
+    println!("speedup: {:.1}x", millis(hashed) / millis(bitset));
+}
+
+/// Returns the average time taken to run the given search, checking that it
+/// gives the right answer each time.
+fn time<F: Fn() -> i32>(search: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        assert_eq!(search(), 56360);
+    }
+
+    start.elapsed() / ITERATIONS
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_nanos()) / 1e6
+}
@@ -0,0 +1,308 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ureq;
+
+use config::Config;
+
+/// The environment variable that the session token is read from.
+pub const SESSION_VAR: &str = "AOC_SESSION";
+
+/// The year of the puzzles that are requested.
+pub const YEAR: u32 = 2018;
+
+const USER_AGENT: &str =
+    "advent_of_code_2018 (https://github.com/ExcaliburZero/advent_of_code_2018)";
+
+/// The file in the cache directory that records when the last request was
+/// made.
+const LAST_REQUEST_FILE: &str = "last_request";
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// No session token was set in the environment or the session file.
+    NoSession(PathBuf),
+    Io(io::Error),
+    /// The server responded with an error status.
+    Status(u16, String),
+    /// The request could not be sent or the response could not be read.
+    Transport(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ClientError::NoSession(ref path) => write!(
+                f, "No session token found, set {} or write it to {}",
+                SESSION_VAR, path.display()
+            ),
+            ClientError::Io(ref err) => write!(f, "{}", err),
+            ClientError::Status(code, ref body) => {
+                write!(f, "Server responded with {}: {}", code, body.trim())
+            }
+            ClientError::Transport(ref err) => write!(f, "Request failed: {}", err),
+        }
+    }
+}
+
+impl Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(err: io::Error) -> ClientError {
+        ClientError::Io(err)
+    }
+}
+
+/// Makes authenticated requests to the puzzle website, leaving at least the
+/// minimum delay between any two requests.
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    min_delay: Duration,
+    cache_dir: PathBuf,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str, min_delay: Duration, cache_dir: &Path) -> Client {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(30))
+            .build();
+
+        Client {
+            agent,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            min_delay,
+            cache_dir: cache_dir.to_path_buf(),
+        }
+    }
+
+    /// Creates a client using the configured website, session token, delay
+    /// and cache directory.
+    pub fn from_config(config: &Config) -> Result<Client, ClientError> {
+        let session = read_session(&config.session_file)?;
+
+        Ok(Client::new(&config.base_url, &session, config.min_delay, &config.cache_dir))
+    }
+
+    /// Requests the given path and returns the response body.
+    pub fn get(&self, path: &str) -> Result<String, ClientError> {
+        self.wait_for_turn()?;
+
+        let request = self.agent.get(&self.url(path))
+            .set("Cookie", &format!("session={}", self.session));
+
+        read_response(request.call())
+    }
+
+    /// Posts the given form to the given path and returns the response body.
+    pub fn post_form(&self, path: &str, form: &[(&str, &str)]) -> Result<String, ClientError> {
+        self.wait_for_turn()?;
+
+        let request = self.agent.post(&self.url(path))
+            .set("Cookie", &format!("session={}", self.session));
+
+        read_response(request.send_form(form))
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Sleeps until the minimum delay has passed since the last request,
+    /// including requests made by earlier runs of the program, and then
+    /// records the current time as the time of the latest request.
+    fn wait_for_turn(&self) -> io::Result<()> {
+        let stamp_path = self.cache_dir.join(LAST_REQUEST_FILE);
+
+        let last_request = fs::read_to_string(&stamp_path).ok()
+            .and_then(|s| s.trim().parse().ok())
+            .map(Duration::from_millis);
+
+        if let Some(last_request) = last_request {
+            let next_allowed = last_request + self.min_delay;
+            let now = now();
+
+            if next_allowed > now {
+                thread::sleep(next_allowed - now);
+            }
+        }
+
+        let now = now();
+        let millis = now.as_secs() * 1000 + u64::from(now.subsec_millis());
+
+        fs::create_dir_all(&self.cache_dir)?;
+        fs::write(&stamp_path, millis.to_string())
+    }
+}
+
+fn read_response(response: Result<ureq::Response, ureq::Error>) -> Result<String, ClientError> {
+    match response {
+        Ok(response) => Ok(response.into_string()?),
+        Err(ureq::Error::Status(code, response)) => {
+            let body = response.into_string().unwrap_or_default();
+
+            Err(ClientError::Status(code, body))
+        }
+        Err(ureq::Error::Transport(err)) => Err(ClientError::Transport(err.to_string())),
+    }
+}
+
+/// Returns the time since the Unix epoch.
+pub fn now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// Reads the session token from the environment, or from the given file if
+/// it is not set there.
+pub fn read_session(session_file: &Path) -> Result<String, ClientError> {
+    let session = match env::var(SESSION_VAR) {
+        Ok(ref session) if !session.trim().is_empty() => session.clone(),
+        _ => fs::read_to_string(session_file).unwrap_or_default(),
+    };
+
+    let session = session.trim();
+    if session.is_empty() {
+        return Err(ClientError::NoSession(session_file.to_path_buf()));
+    }
+
+    Ok(session.to_string())
+}
+
+/// A minimal HTTP server for testing the clients against, which serves
+/// canned responses and records the requests that it receives.
+#[cfg(test)]
+pub mod stub {
+    use std::env;
+    use std::fs;
+    use std::io::prelude::*;
+    use std::io::BufReader;
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::process;
+    use std::thread;
+
+    /// Serves the given status codes and bodies to the next connections, in
+    /// order. Returns the base URL of the server and a handle that gives the
+    /// received requests once all of the responses have been served.
+    pub fn serve(responses: Vec<(u16, &'static str)>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+
+            for (code, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+
+                    let lower = line.to_lowercase();
+                    if let Some(length) = lower.strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+
+                    request.push_str(&line);
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                }
+
+                let mut content = vec![0; content_length];
+                reader.read_exact(&mut content).unwrap();
+                request.push_str(&String::from_utf8(content).unwrap());
+
+                let response = format!(
+                    "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    code, body.len(), body
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+
+                requests.push(request);
+            }
+
+            requests
+        });
+
+        (base_url, handle)
+    }
+
+    /// Returns a new empty temporary directory for the given test.
+    pub fn temp_dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir()
+            .join(format!("advent_of_code_2018-{}-{}", test, process::id()));
+
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Instant;
+
+    #[test]
+    fn get_it_sends_the_session_cookie() {
+        let (base_url, server) = stub::serve(vec![(200, "+1\n-2\n")]);
+        let cache_dir = stub::temp_dir("get_cookie");
+
+        let client = Client::new(&base_url, "abc123", Duration::from_secs(0), &cache_dir);
+        let actual = client.get("/2018/day/1/input").unwrap();
+
+        let requests = server.join().unwrap();
+
+        assert_eq!(actual, "+1\n-2\n");
+        assert!(requests[0].starts_with("GET /2018/day/1/input HTTP/1.1"));
+        assert!(requests[0].contains("session=abc123"));
+    }
+
+    #[test]
+    fn get_it_reports_error_statuses() {
+        let (base_url, server) = stub::serve(vec![(400, "Please log in")]);
+        let cache_dir = stub::temp_dir("get_status");
+
+        let client = Client::new(&base_url, "abc123", Duration::from_secs(0), &cache_dir);
+        let actual = client.get("/2018/day/1/input");
+
+        server.join().unwrap();
+
+        match actual {
+            Err(ClientError::Status(400, ref body)) => assert_eq!(body, "Please log in"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn get_it_waits_between_requests() {
+        let (base_url, server) = stub::serve(vec![(200, "a"), (200, "b")]);
+        let cache_dir = stub::temp_dir("get_delay");
+        let delay = Duration::from_millis(300);
+
+        let client = Client::new(&base_url, "abc123", delay, &cache_dir);
+
+        let start = Instant::now();
+        client.get("/a").unwrap();
+        client.get("/b").unwrap();
+
+        server.join().unwrap();
+
+        assert!(start.elapsed() >= delay);
+    }
+}
@@ -0,0 +1,354 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use toml;
+
+/// The name of the configuration file that is searched for.
+pub const FILE_NAME: &str = "aoc.toml";
+
+/// The name of the directory used for this program under the XDG config and
+/// cache directories.
+pub const APP_DIR: &str = "advent_of_code_2018";
+
+/// The keys that can be set in the configuration file. Each of them can also
+/// be overridden on the command line by the flag of the same name, with
+/// underscores replaced by dashes.
+pub const KEYS: [&str; 8] = [
+    "input_dir",
+    "input_pattern",
+    "format",
+    "timeout",
+    "cache_dir",
+    "base_url",
+    "session_file",
+    "min_delay",
+];
+
+/// The website that puzzle inputs are fetched from by default.
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// The format that answers and reports are printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<OutputFormat, ()> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// The effective settings, after combining the defaults, the configuration
+/// file and the command line flags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The directory that puzzle inputs are read from.
+    pub input_dir: PathBuf,
+    /// The file name of a day's input, with `{day}` replaced by the day
+    /// number.
+    pub input_pattern: String,
+    pub format: OutputFormat,
+    /// How long a single part may run before it is abandoned.
+    pub timeout: Option<Duration>,
+    pub cache_dir: PathBuf,
+    /// The address of the puzzle website, without a trailing slash.
+    pub base_url: String,
+    /// The file that the session token is read from, if it is not set in the
+    /// environment.
+    pub session_file: PathBuf,
+    /// The minimum time to leave between requests to the puzzle website.
+    pub min_delay: Duration,
+    /// The configuration file that was read, if any.
+    pub source: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    UnknownKey(String),
+    InvalidValue(String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Io(ref path, ref err) => {
+                write!(f, "Could not read {}: {}", path.display(), err)
+            }
+            ConfigError::Parse(ref path, ref err) => {
+                write!(f, "Could not parse {}: {}", path.display(), err)
+            }
+            ConfigError::UnknownKey(ref key) => {
+                write!(f, "Unknown configuration key: {}", key)
+            }
+            ConfigError::InvalidValue(ref key, ref value) => {
+                write!(f, "Invalid value for {}: {}", key, value)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            input_dir: PathBuf::from("inputs/"),
+            input_pattern: "{day}.txt".to_string(),
+            format: OutputFormat::Text,
+            timeout: None,
+            cache_dir: default_cache_dir(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            session_file: xdg_dir("XDG_CONFIG_HOME", ".config")
+                .unwrap_or_default()
+                .join("session"),
+            min_delay: Duration::from_secs(5),
+            source: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the settings from the first configuration file found in the
+    /// working directory or the XDG config directory, falling back to the
+    /// defaults if there is none.
+    pub fn discover() -> Result<Config, ConfigError> {
+        let mut candidates = vec![PathBuf::from(FILE_NAME)];
+        if let Some(dir) = xdg_dir("XDG_CONFIG_HOME", ".config") {
+            candidates.push(dir.join(FILE_NAME));
+        }
+
+        match candidates.into_iter().find(|path| path.is_file()) {
+            Some(path) => Config::load(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Loads the settings from the given configuration file, using the
+    /// defaults for any keys that it does not set.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+
+        let mut config = Config::parse(&contents)
+            .map_err(|e| match e {
+                ConfigError::Parse(_, err) => {
+                    ConfigError::Parse(path.to_path_buf(), err)
+                }
+                e => e,
+            })?;
+        config.source = Some(path.to_path_buf());
+
+        Ok(config)
+    }
+
+    /// Parses the given configuration file contents on top of the defaults.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use advent_of_code_2018::config::{Config, OutputFormat};
+    ///
+    /// let config = Config::parse("format = \"json\"\ntimeout = 5\n").unwrap();
+    ///
+    /// assert_eq!(config.format, OutputFormat::Json);
+    /// assert_eq!(config.timeout, Some(Duration::from_secs(5)));
+    /// assert_eq!(config.input_pattern, "{day}.txt");
+    /// ```
+    pub fn parse(contents: &str) -> Result<Config, ConfigError> {
+        let table: toml::value::Table = toml::from_str(contents)
+            .map_err(|e| ConfigError::Parse(PathBuf::from(FILE_NAME), e))?;
+
+        let mut config = Config::default();
+        for (key, value) in &table {
+            let value = match *value {
+                toml::Value::String(ref s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                ref other => {
+                    return Err(ConfigError::InvalidValue(key.clone(), other.to_string()))
+                }
+            };
+
+            config.set(key, &value)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Sets the given key to the given value, as written in the
+    /// configuration file or passed on the command line.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = || ConfigError::InvalidValue(key.to_string(), value.to_string());
+
+        match key {
+            "input_dir" => { self.input_dir = PathBuf::from(value); }
+            "input_pattern" => { self.input_pattern = value.to_string(); }
+            "format" => { self.format = value.parse().map_err(|_| invalid())?; }
+            "timeout" => {
+                let seconds: u64 = value.parse().map_err(|_| invalid())?;
+
+                // A timeout of zero disables the timeout
+                self.timeout = if seconds == 0 {
+                    None
+                } else {
+                    Some(Duration::from_secs(seconds))
+                };
+            }
+            "cache_dir" => { self.cache_dir = PathBuf::from(value); }
+            "base_url" => {
+                self.base_url = value.trim_end_matches('/').to_string();
+            }
+            "session_file" => { self.session_file = PathBuf::from(value); }
+            "min_delay" => {
+                let seconds = value.parse().map_err(|_| invalid())?;
+
+                self.min_delay = Duration::from_secs(seconds);
+            }
+            _ => { return Err(ConfigError::UnknownKey(key.to_string())); }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path of the input file for the given day.
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use advent_of_code_2018::config::Config;
+    ///
+    /// let config = Config::default();
+    ///
+    /// assert_eq!(config.input_path(3), PathBuf::from("inputs/3.txt"));
+    /// ```
+    pub fn input_path(&self, day: u32) -> PathBuf {
+        let file_name = self.input_pattern.replace("{day}", &day.to_string());
+
+        self.input_dir.join(file_name)
+    }
+}
+
+/// Prints out the settings in the same format as the configuration file.
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.source {
+            Some(ref path) => writeln!(f, "# Read from {}", path.display())?,
+            None => writeln!(f, "# No {} found, using defaults", FILE_NAME)?,
+        }
+
+        let timeout = self.timeout.map(|t| t.as_secs()).unwrap_or(0);
+
+        writeln!(f, "input_dir = {:?}", self.input_dir.display().to_string())?;
+        writeln!(f, "input_pattern = {:?}", self.input_pattern)?;
+        writeln!(f, "format = \"{}\"", self.format)?;
+        writeln!(f, "timeout = {}", timeout)?;
+        writeln!(f, "cache_dir = {:?}", self.cache_dir.display().to_string())?;
+        writeln!(f, "base_url = {:?}", self.base_url)?;
+        writeln!(f, "session_file = {:?}", self.session_file.display().to_string())?;
+        writeln!(f, "min_delay = {}", self.min_delay.as_secs())
+    }
+}
+
+/// Returns this program's directory under the given XDG base directory,
+/// falling back to the given directory under the home directory if the
+/// variable is not set.
+pub fn xdg_dir(variable: &str, home_fallback: &str) -> Option<PathBuf> {
+    let base = match env::var_os(variable) {
+        Some(ref dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(home_fallback),
+    };
+
+    Some(base.join(APP_DIR))
+}
+
+fn default_cache_dir() -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+        .unwrap_or_else(|| PathBuf::from(".cache"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_it_uses_defaults_for_empty_file() {
+        let config = Config::parse("").unwrap();
+
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn parse_it_reads_all_keys() {
+        let contents = "input_dir = \"data\"\n\
+                        input_pattern = \"day{day}.in\"\n\
+                        format = \"json\"\n\
+                        timeout = 30\n\
+                        cache_dir = \"/tmp/aoc\"\n\
+                        base_url = \"http://127.0.0.1:8080/\"\n\
+                        session_file = \"token\"\n\
+                        min_delay = 0\n";
+
+        let config = Config::parse(contents).unwrap();
+
+        assert_eq!(config.input_path(12), PathBuf::from("data/day12.in"));
+        assert_eq!(config.format, OutputFormat::Json);
+        assert_eq!(config.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(config.cache_dir, PathBuf::from("/tmp/aoc"));
+        assert_eq!(config.base_url, "http://127.0.0.1:8080");
+        assert_eq!(config.session_file, PathBuf::from("token"));
+        assert_eq!(config.min_delay, Duration::from_secs(0));
+    }
+
+    #[test]
+    fn parse_it_rejects_unknown_keys() {
+        let actual = Config::parse("colour = \"red\"\n");
+
+        match actual {
+            Err(ConfigError::UnknownKey(ref key)) => assert_eq!(key, "colour"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_it_rejects_invalid_values() {
+        let actual = Config::parse("format = \"yaml\"\n");
+
+        match actual {
+            Err(ConfigError::InvalidValue(ref key, _)) => assert_eq!(key, "format"),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn set_it_overrides_file_values() {
+        let mut config = Config::parse("timeout = 30\n").unwrap();
+
+        config.set("timeout", "0").unwrap();
+        config.set("input_dir", "other").unwrap();
+
+        assert_eq!(config.timeout, None);
+        assert_eq!(config.input_path(1), PathBuf::from("other/1.txt"));
+    }
+}
@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::PathBuf;
+
+use client::{Client, ClientError, YEAR};
+use config::Config;
+
+/// Where a day's input was found.
+#[derive(Debug, PartialEq)]
+pub enum Fetched {
+    /// The input was already in the inputs directory, so it was not
+    /// downloaded again.
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+/// Makes sure that the input for the given day is in the inputs directory,
+/// downloading it if it is not already there.
+///
+/// The client is only created if the input needs to be downloaded, so no
+/// session token is needed for cached inputs.
+pub fn fetch_input<F>(config: &Config, day: u32, client: F) -> Result<Fetched, ClientError>
+    where F: FnOnce() -> Result<Client, ClientError> {
+    let path = config.input_path(day);
+
+    let is_cached = fs::metadata(&path).map(|m| m.len() > 0).unwrap_or(false);
+    if is_cached {
+        return Ok(Fetched::Cached(path));
+    }
+
+    let input = client()?.get(&format!("/{}/day/{}/input", YEAR, day))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, input)?;
+
+    Ok(Fetched::Downloaded(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use client::stub;
+
+    fn test_config(test: &str, base_url: &str) -> Config {
+        let dir = stub::temp_dir(test);
+
+        Config {
+            input_dir: dir.join("inputs"),
+            cache_dir: dir.join("cache"),
+            base_url: base_url.to_string(),
+            min_delay: Duration::from_secs(0),
+            ..Config::default()
+        }
+    }
+
+    fn test_client(config: &Config) -> Result<Client, ClientError> {
+        Ok(Client::new(&config.base_url, "abc123", config.min_delay, &config.cache_dir))
+    }
+
+    #[test]
+    fn fetch_input_it_downloads_missing_inputs() {
+        let (base_url, server) = stub::serve(vec![(200, "+1\n-2\n")]);
+        let config = test_config("fetch_missing", &base_url);
+
+        let actual = fetch_input(&config, 4, || test_client(&config)).unwrap();
+
+        let requests = server.join().unwrap();
+        let path = config.input_path(4);
+
+        assert_eq!(actual, Fetched::Downloaded(path.clone()));
+        assert_eq!(fs::read_to_string(path).unwrap(), "+1\n-2\n");
+        assert!(requests[0].starts_with("GET /2018/day/4/input "));
+    }
+
+    #[test]
+    fn fetch_input_it_does_not_download_cached_inputs() {
+        let config = test_config("fetch_cached", "http://127.0.0.1:9");
+
+        fs::create_dir_all(&config.input_dir).unwrap();
+        fs::write(config.input_path(1), "+3\n").unwrap();
+
+        let actual = fetch_input(&config, 1, || panic!("Should not connect"));
+
+        assert_eq!(actual.unwrap(), Fetched::Cached(config.input_path(1)));
+    }
+
+    #[test]
+    fn fetch_input_it_does_not_save_failed_downloads() {
+        let (base_url, server) = stub::serve(vec![(404, "Not found")]);
+        let config = test_config("fetch_failed", &base_url);
+
+        let actual = fetch_input(&config, 26, || test_client(&config));
+
+        server.join().unwrap();
+
+        assert!(actual.is_err());
+        assert!(!config.input_path(26).exists());
+    }
+}
@@ -0,0 +1,432 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+use serde::de::Error;
+use serde_json::{self, Value};
+
+/// The number of puzzle days in an event.
+pub const DAYS: u32 = 25;
+
+/// A private leaderboard, as exported from the website's JSON API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leaderboard {
+    pub event: i32,
+    pub members: Vec<Member>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub id: i64,
+    pub name: String,
+    pub stars: u32,
+    pub local_score: i64,
+    /// The times that each star was earned at, in seconds since the Unix
+    /// epoch, by day and then part.
+    pub completions: BTreeMap<u32, [Option<i64>; 2]>,
+}
+
+impl Member {
+    /// Returns the time that the given part of the given day was completed
+    /// at, if it was completed.
+    pub fn completed_at(&self, day: u32, part: usize) -> Option<i64> {
+        self.completions.get(&day).and_then(|parts| parts[part])
+    }
+
+    /// Returns the time of the member's latest star.
+    pub fn last_star(&self) -> Option<i64> {
+        self.completions.values()
+            .flat_map(|parts| parts.iter().filter_map(|t| *t))
+            .max()
+    }
+}
+
+#[derive(Deserialize)]
+struct RawLeaderboard {
+    #[serde(deserialize_with = "flexible_int")]
+    event: i64,
+    members: HashMap<String, RawMember>,
+}
+
+#[derive(Deserialize)]
+struct RawMember {
+    #[serde(deserialize_with = "flexible_int")]
+    id: i64,
+    name: Option<String>,
+    stars: u32,
+    local_score: i64,
+    #[serde(default)]
+    completion_day_level: HashMap<String, HashMap<String, RawStar>>,
+}
+
+#[derive(Deserialize)]
+struct RawStar {
+    #[serde(deserialize_with = "flexible_int")]
+    get_star_ts: i64,
+}
+
+/// Reads an integer that may be written either as a number or as a string,
+/// since older exports quote their ids and timestamps.
+fn flexible_int<'de, D>(deserializer: D) -> Result<i64, D::Error>
+    where D: Deserializer<'de> {
+    match Value::deserialize(deserializer)? {
+        Value::Number(ref n) if n.is_i64() => Ok(n.as_i64().unwrap()),
+        Value::String(ref s) => s.parse().map_err(D::Error::custom),
+        other => Err(D::Error::custom(format!("expected an integer, found {}", other))),
+    }
+}
+
+/// Parses an exported leaderboard.
+///
+/// Members are ordered by id, and anonymous members are given the same name
+/// that the website shows for them.
+pub fn parse(json: &str) -> Result<Leaderboard, serde_json::Error> {
+    let raw: RawLeaderboard = serde_json::from_str(json)?;
+
+    let mut members: Vec<Member> = raw.members.into_values()
+        .map(|m| {
+            let mut completions = BTreeMap::new();
+            for (day, parts) in m.completion_day_level {
+                let day = day.parse().map_err(serde_json::Error::custom)?;
+
+                let mut times = [None, None];
+                for (part, star) in parts {
+                    match part.as_str() {
+                        "1" => times[0] = Some(star.get_star_ts),
+                        "2" => times[1] = Some(star.get_star_ts),
+                        p => return Err(serde_json::Error::custom(format!("unknown part {}", p))),
+                    }
+                }
+
+                completions.insert(day, times);
+            }
+
+            let id = m.id;
+
+            Ok(Member {
+                id,
+                name: m.name.unwrap_or_else(|| format!("(anonymous user #{})", id)),
+                stars: m.stars,
+                local_score: m.local_score,
+                completions,
+            })
+        })
+        .collect::<Result<_, serde_json::Error>>()?;
+
+    members.sort_by_key(|m| m.id);
+
+    let event = i32::try_from(raw.event).map_err(serde_json::Error::custom)?;
+
+    Ok(Leaderboard { event, members })
+}
+
+/// Returns the time that the given day's puzzle unlocked at, which is
+/// midnight US Eastern time, in seconds since the Unix epoch.
+///
+/// ```
+/// use advent_of_code_2018::leaderboard;
+///
+/// assert_eq!(leaderboard::unlock_time(2018, 1), 1543640400);
+/// ```
+pub fn unlock_time(year: i32, day: u32) -> i64 {
+    days_from_civil(year, 12, day) * 86400 + 5 * 3600
+}
+
+/// Returns the number of days between the Unix epoch and the given date.
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    // Treat January and February as the end of the previous year, so that
+    // the leap day is the last day of the year
+    let year = i64::from(if month <= 2 { year - 1 } else { year });
+    let month = i64::from(month);
+    let day = i64::from(day);
+
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+/// Recomputes the local score of each member by id. For each star, the first
+/// member to earn it gets as many points as there are members, the second
+/// gets one fewer, and so on.
+pub fn local_scores(leaderboard: &Leaderboard) -> HashMap<i64, i64> {
+    let num_members = leaderboard.members.len() as i64;
+
+    let mut scores: HashMap<i64, i64> = leaderboard.members.iter()
+        .map(|m| (m.id, 0))
+        .collect();
+
+    for day in 1..=DAYS {
+        for part in 0..2 {
+            let mut finishers: Vec<(i64, i64)> = leaderboard.members.iter()
+                .filter_map(|m| m.completed_at(day, part).map(|t| (t, m.id)))
+                .collect();
+            finishers.sort();
+
+            for (rank, &(_, id)) in finishers.iter().enumerate() {
+                *scores.get_mut(&id).unwrap() += num_members - rank as i64;
+            }
+        }
+    }
+
+    scores
+}
+
+/// A summary of a leaderboard, with the standings and how long each member
+/// took on each day.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub event: i32,
+    pub rankings: Vec<Ranking>,
+    pub days: Vec<DayReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Ranking {
+    pub rank: usize,
+    pub id: i64,
+    pub name: String,
+    pub stars: u32,
+    /// The local score given in the export.
+    pub local_score: i64,
+    /// The local score recomputed from the star times.
+    pub recomputed_score: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayReport {
+    pub day: u32,
+    pub completions: Vec<Completion>,
+}
+
+/// How long a member took to complete the parts of a day, in seconds since
+/// the puzzle unlocked.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Completion {
+    pub id: i64,
+    pub name: String,
+    pub part_one: Option<i64>,
+    pub part_two: Option<i64>,
+    /// The time between completing part one and part two.
+    pub delta: Option<i64>,
+}
+
+/// Builds the report for the given leaderboard.
+///
+/// Members are ranked by their recomputed local score, with ties going to
+/// the member who earned their last star first. Each day's completions are
+/// ordered by part two time and then part one time, and days with no
+/// completions are left out.
+pub fn report(leaderboard: &Leaderboard) -> Report {
+    let scores = local_scores(leaderboard);
+
+    let mut members: Vec<&Member> = leaderboard.members.iter().collect();
+    members.sort_by_key(|m| {
+        (-scores[&m.id], m.last_star().unwrap_or(i64::MAX), m.id)
+    });
+
+    let rankings = members.iter().enumerate()
+        .map(|(i, m)| Ranking {
+            rank: i + 1,
+            id: m.id,
+            name: m.name.clone(),
+            stars: m.stars,
+            local_score: m.local_score,
+            recomputed_score: scores[&m.id],
+        })
+        .collect();
+
+    let mut days = Vec::new();
+    for day in 1..=DAYS {
+        let unlock = unlock_time(leaderboard.event, day);
+
+        let mut completions: Vec<Completion> = leaderboard.members.iter()
+            .filter(|m| m.completions.contains_key(&day))
+            .map(|m| {
+                let part_one = m.completed_at(day, 0).map(|t| t - unlock);
+                let part_two = m.completed_at(day, 1).map(|t| t - unlock);
+
+                Completion {
+                    id: m.id,
+                    name: m.name.clone(),
+                    part_one,
+                    part_two,
+                    delta: part_two.and_then(|two| part_one.map(|one| two - one)),
+                }
+            })
+            .collect();
+
+        if completions.is_empty() {
+            continue;
+        }
+
+        completions.sort_by_key(|c| {
+            (c.part_two.unwrap_or(i64::MAX), c.part_one.unwrap_or(i64::MAX), c.id)
+        });
+
+        days.push(DayReport { day, completions });
+    }
+
+    Report { event: leaderboard.event, rankings, days }
+}
+
+/// Formats a number of seconds as hours, minutes and seconds.
+fn format_duration(seconds: Option<i64>) -> String {
+    match seconds {
+        Some(s) => format!("{}:{:02}:{:02}", s / 3600, s % 3600 / 60, s % 60),
+        None => "-".to_string(),
+    }
+}
+
+/// Prints the report as tables.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name_width = self.rankings.iter()
+            .map(|r| r.name.chars().count())
+            .chain(Some(4))
+            .max()
+            .unwrap();
+
+        writeln!(f, "Advent of Code {}", self.event)?;
+        writeln!(f)?;
+        writeln!(
+            f, "{:>4}  {:<w$}  {:>5}  {:>5}  {:>10}",
+            "Rank", "Name", "Stars", "Score", "Recomputed", w = name_width
+        )?;
+        for r in &self.rankings {
+            writeln!(
+                f, "{:>4}  {:<w$}  {:>5}  {:>5}  {:>10}",
+                r.rank, r.name, r.stars, r.local_score, r.recomputed_score, w = name_width
+            )?;
+        }
+
+        for day in &self.days {
+            writeln!(f)?;
+            writeln!(f, "Day {}", day.day)?;
+            writeln!(
+                f, "  {:<w$}  {:>10}  {:>10}  {:>10}",
+                "Name", "Part one", "Part two", "Delta", w = name_width
+            )?;
+            for c in &day.completions {
+                writeln!(
+                    f, "  {:<w$}  {:>10}  {:>10}  {:>10}",
+                    c.name,
+                    format_duration(c.part_one),
+                    format_duration(c.part_two),
+                    format_duration(c.delta),
+                    w = name_width
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "owner_id": "100",
+        "event": "2018",
+        "members": {
+            "100": {
+                "id": "100", "name": "Alice", "stars": 3, "local_score": 8,
+                "global_score": 0, "last_star_ts": "1543727700",
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": "1543641000"},
+                        "2": {"get_star_ts": "1543642200"}
+                    },
+                    "2": {"1": {"get_star_ts": "1543727700"}}
+                }
+            },
+            "200": {
+                "id": 200, "name": null, "stars": 2, "local_score": 5,
+                "global_score": 0, "last_star_ts": 1543641300,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1543640700},
+                        "2": {"get_star_ts": 1543641300}
+                    }
+                }
+            },
+            "300": {
+                "id": "300", "name": "Carol", "stars": 0, "local_score": 0,
+                "global_score": 0, "last_star_ts": "0",
+                "completion_day_level": {}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn parse_it_reads_quoted_and_unquoted_values() {
+        let leaderboard = parse(EXPORT).unwrap();
+
+        assert_eq!(leaderboard.event, 2018);
+        assert_eq!(leaderboard.members.len(), 3);
+
+        let alice = &leaderboard.members[0];
+        assert_eq!(alice.name, "Alice");
+        assert_eq!(alice.completed_at(1, 1), Some(1543642200));
+        assert_eq!(alice.completed_at(2, 1), None);
+
+        let anonymous = &leaderboard.members[1];
+        assert_eq!(anonymous.name, "(anonymous user #200)");
+        assert_eq!(anonymous.completed_at(1, 0), Some(1543640700));
+    }
+
+    #[test]
+    fn parse_it_rejects_malformed_exports() {
+        assert!(parse("{\"event\": \"2018\"}").is_err());
+        assert!(parse("{\"event\": \"4294969314\", \"members\": {}}").is_err());
+    }
+
+    #[test]
+    fn unlock_time_it_works_across_years() {
+        assert_eq!(unlock_time(2018, 25), 1545714000);
+        assert_eq!(unlock_time(2020, 1), 1606798800);
+    }
+
+    #[test]
+    fn local_scores_it_awards_points_by_finishing_order() {
+        let leaderboard = parse(EXPORT).unwrap();
+
+        let scores = local_scores(&leaderboard);
+
+        assert_eq!(scores[&100], 2 + 2 + 3);
+        assert_eq!(scores[&200], 3 + 3);
+        assert_eq!(scores[&300], 0);
+    }
+
+    #[test]
+    fn report_it_ranks_members_and_times_days() {
+        let leaderboard = parse(EXPORT).unwrap();
+
+        let report = report(&leaderboard);
+
+        let ranked: Vec<i64> = report.rankings.iter().map(|r| r.id).collect();
+        assert_eq!(ranked, vec![100, 200, 300]);
+
+        assert_eq!(report.days.len(), 2);
+        let day_one = &report.days[0].completions;
+        assert_eq!(day_one[0].id, 200);
+        assert_eq!(day_one[0].part_one, Some(300));
+        assert_eq!(day_one[0].delta, Some(600));
+        assert_eq!(day_one[1].part_two, Some(1800));
+
+        let day_two = &report.days[1].completions;
+        assert_eq!(day_two[0].part_two, None);
+        assert_eq!(day_two[0].delta, None);
+    }
+
+    #[test]
+    fn format_duration_it_allows_more_than_a_day() {
+        assert_eq!(format_duration(Some(90061)), "25:01:01");
+        assert_eq!(format_duration(None), "-");
+    }
+}
@@ -1,3 +1,19 @@
+extern crate memmap2;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate toml;
+extern crate ureq;
+
+pub mod client;
+pub mod config;
+pub mod fetch;
+pub mod leaderboard;
+pub mod report;
+pub mod solutions;
+pub mod submit;
+
 pub mod one;
 pub mod two;
 pub mod three;
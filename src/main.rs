@@ -1,60 +1,739 @@
 extern crate clap;
-use clap::{Arg, App, SubCommand};
+#[macro_use]
+extern crate serde_json;
+extern crate terminal_size;
+use clap::{AppSettings, Arg, ArgMatches, App, SubCommand};
+
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::process;
 
 extern crate advent_of_code_2018;
 
+use advent_of_code_2018::client::Client;
+use advent_of_code_2018::config::{self, Config, OutputFormat};
+use advent_of_code_2018::fetch::{self, Fetched};
+use advent_of_code_2018::leaderboard;
+use advent_of_code_2018::one::{self, Merge, Query, Strategy, Width};
+use advent_of_code_2018::report;
+use advent_of_code_2018::solutions::{self, Answer, Part, Solution};
+use advent_of_code_2018::submit::{self, SubmissionLog};
+use advent_of_code_2018::two::{self, BkTree, Metric};
+
 fn main() {
     let matches =
         App::new("advent_of_code_2018")
             .version("day1")
             .author("Christopher Wells <cwellsny@nycap.rr.com>")
-            .subcommand(SubCommand::with_name("day1")
-                .arg(Arg::with_name("part")
-                    .help("Selects the part to run (one, two)")
+            .args(&config_args())
+            .arg(Arg::with_name("verbose")
+                .help("Prints more details along with the answers")
+                .short("v")
+                .long("verbose")
+                .global(true)
+            )
+            .subcommand(day_subcommand("day1")
+                .setting(AppSettings::AllowNegativeNumbers)
+                .arg(Arg::with_name("strategy")
+                    .help("Selects how part two searches for the repeat (simulation, analytic)")
+                    .long("strategy")
+                    .value_name("STRATEGY")
+                )
+                .arg(Arg::with_name("width")
+                    .help("Selects the integer type of the frequencies (i32, i64, i128)")
+                    .long("width")
+                    .value_name("TYPE")
+                )
+                .arg(Arg::with_name("stream")
+                    .help("Sums part one without reading the whole input into memory")
+                    .long("stream")
+                )
+                .arg(Arg::with_name("merge")
+                    .help("Selects how the merge part interleaves the devices (round-robin, timestamp)")
+                    .long("merge")
+                    .value_name("POLICY")
+                )
+                .arg(Arg::with_name("value")
+                    .help("The value for the extra parts: times <k>, repeats <n>, visits <passes>, \
+                           reach <target>, whatif [edits] or merge <files>...")
+                    .allow_hyphen_values(true)
+                    .multiple(true)
+                    .index(2)
+                )
+            )
+            .subcommand(day_subcommand("day2")
+                .arg(Arg::with_name("metric")
+                    .help("Selects how the pairs part measures distance (hamming, levenshtein)")
+                    .long("metric")
+                    .value_name("METRIC")
+                )
+                .arg(Arg::with_name("radius")
+                    .help("Makes the nearest part list every ID within the given distance")
+                    .long("radius")
+                    .value_name("DISTANCE")
+                )
+                .arg(Arg::with_name("count")
+                    .help("Selects how many IDs the nearest part lists")
+                    .long("count")
+                    .value_name("COUNT")
+                )
+                .arg(Arg::with_name("value")
+                    .help("The value for the extra parts: pairs <distance> or nearest <id>")
+                    .index(2)
+                )
+            )
+            .subcommand(day_subcommand("day3"))
+            .subcommand(SubCommand::with_name("fetch")
+                .about("Downloads the input for a day into the inputs directory")
+                .arg(Arg::with_name("day")
+                    .help("The day to download the input for")
                     .required(true)
                     .index(1)
                 )
             )
-            .subcommand(SubCommand::with_name("day2")
-                .arg(Arg::with_name("part")
-                    .help("Selects the part to run (one, two)")
+            .subcommand(SubCommand::with_name("submit")
+                .about("Submits the answer to a part of a day to the puzzle website")
+                .arg(Arg::with_name("day")
+                    .help("The day to submit the answer for")
                     .required(true)
                     .index(1)
                 )
-            )
-            .subcommand(SubCommand::with_name("day3")
                 .arg(Arg::with_name("part")
-                    .help("Selects the part to run (one, two)")
+                    .help("The part to submit the answer for (one, two)")
+                    .required(true)
+                    .index(2)
+                )
+                .arg(input_arg())
+            )
+            .subcommand(SubCommand::with_name("leaderboard")
+                .about("Reports the standings of an exported private leaderboard")
+                .arg(Arg::with_name("file")
+                    .help("The leaderboard JSON file")
                     .required(true)
                     .index(1)
                 )
             )
+            .subcommand(SubCommand::with_name("report")
+                .about("Runs every day and updates the results section of the README")
+                .arg(Arg::with_name("readme")
+                    .help("The file to update the results section of")
+                    .long("readme")
+                    .value_name("FILE")
+                    .default_value("README.md")
+                )
+            )
+            .subcommand(SubCommand::with_name("config")
+                .about("Inspects the settings read from aoc.toml")
+                .subcommand(SubCommand::with_name("show")
+                    .about("Prints the effective settings")
+                )
+            )
         .get_matches()
     ;
 
-    if let Some(matches) = matches.subcommand_matches("day1") {
-        let part = matches.value_of("part").unwrap();
+    let config = load_config(&matches).unwrap_or_else(|e| fail(e));
+
+    match matches.subcommand() {
+        ("day1", Some(matches)) => { run_day(&config, 1, matches); }
+        ("day2", Some(matches)) => { run_day(&config, 2, matches); }
+        ("day3", Some(matches)) => { run_day(&config, 3, matches); }
+        ("fetch", Some(matches)) => { run_fetch(&config, matches); }
+        ("submit", Some(matches)) => { run_submit(&config, matches); }
+        ("leaderboard", Some(matches)) => { run_leaderboard(&config, matches); }
+        ("report", Some(matches)) => { run_report(&config, matches); }
+        ("config", Some(_)) => { print!("{}", config); }
+        _ => {}
+    }
+}
+
+/// The flags that override the settings in the configuration file.
+fn config_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("input-dir")
+            .help("Directory to read the puzzle inputs from")
+            .long("input-dir")
+            .value_name("DIR")
+            .global(true),
+        Arg::with_name("input-pattern")
+            .help("File name of the puzzle inputs, with {day} for the day number")
+            .long("input-pattern")
+            .value_name("PATTERN")
+            .global(true),
+        Arg::with_name("format")
+            .help("Format to print the output in (text, json)")
+            .long("format")
+            .value_name("FORMAT")
+            .global(true),
+        Arg::with_name("timeout")
+            .help("Seconds that a part may run for, or 0 for no limit")
+            .long("timeout")
+            .value_name("SECONDS")
+            .global(true),
+        Arg::with_name("cache-dir")
+            .help("Directory to keep cached data in")
+            .long("cache-dir")
+            .value_name("DIR")
+            .global(true),
+        Arg::with_name("base-url")
+            .help("Address of the puzzle website")
+            .long("base-url")
+            .value_name("URL")
+            .global(true),
+        Arg::with_name("session-file")
+            .help("File to read the session token from if AOC_SESSION is not set")
+            .long("session-file")
+            .value_name("FILE")
+            .global(true),
+        Arg::with_name("min-delay")
+            .help("Seconds to wait between requests to the puzzle website")
+            .long("min-delay")
+            .value_name("SECONDS")
+            .global(true),
+    ]
+}
+
+fn day_subcommand<'a, 'b>(name: &'a str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .arg(Arg::with_name("part")
+            .help("Selects the part to run (one, two)")
+            .required(true)
+            .index(1)
+        )
+        .arg(input_arg())
+}
+
+fn input_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("input")
+        .help("Reads the input from the given file, or stdin for -")
+        .long("input")
+        .value_name("FILE")
+}
+
+/// Reads the configuration file and applies any overrides given on the
+/// command line.
+fn load_config(matches: &ArgMatches) -> Result<Config, config::ConfigError> {
+    let mut config = Config::discover()?;
+
+    for key in config::KEYS.iter() {
+        if let Some(value) = global_value(matches, &key.replace('_', "-")) {
+            config.set(key, value)?;
+        }
+    }
+
+    Ok(config)
+}
+
+/// Returns the value of the given global flag, wherever it was given on the
+/// command line.
+fn global_value<'a>(matches: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    let (_, subcommand) = matches.subcommand();
+
+    subcommand
+        .and_then(|m| global_value(m, name))
+        .or_else(|| matches.value_of(name))
+}
+
+/// A part of a day beyond the two parts of the puzzle, which is run from
+/// the command line in its own way rather than as a `Solution`.
+struct ExtraPart {
+    day: u32,
+    part: &'static str,
+    run: fn(&Config, &ArgMatches),
+}
+
+/// All of the extra parts, in order.
+static EXTRA_PARTS: [ExtraPart; 9] = [
+    ExtraPart { day: 1, part: "times", run: run_day1_query },
+    ExtraPart { day: 1, part: "repeats", run: run_day1_query },
+    ExtraPart { day: 1, part: "visits", run: run_day1_query },
+    ExtraPart { day: 1, part: "reach", run: run_day1_query },
+    ExtraPart { day: 1, part: "report", run: run_day1_report },
+    ExtraPart { day: 1, part: "whatif", run: run_day1_what_if },
+    ExtraPart { day: 1, part: "merge", run: run_day1_merge },
+    ExtraPart { day: 2, part: "pairs", run: run_day2_pairs },
+    ExtraPart { day: 2, part: "nearest", run: run_day2_nearest },
+];
+
+fn run_day(config: &Config, day: u32, matches: &ArgMatches) {
+    let part = matches.value_of("part").unwrap();
+
+    if let Some(extra) = EXTRA_PARTS.iter().find(|extra| extra.day == day && extra.part == part) {
+        (extra.run)(config, matches);
+        return;
+    }
+
+    let solution = match part.parse().ok().and_then(|p| solutions::find(day, p)) {
+        Some(solution) => solution,
+        None => { println!("Unknown part: {}", part); return; }
+    };
+
+    if day == 1 {
+        run_day1(config, solution.part, matches);
+        return;
+    }
+
+    let answer = solve(config, solution, matches.value_of("input"));
+
+    print_answer(config.format, day, solution.part, &answer);
+}
+
+fn run_day1(config: &Config, part: Part, matches: &ArgMatches) {
+    let width = day1_width(matches);
+    let strategy = match matches.value_of("strategy") {
+        Some(strategy) => strategy.parse()
+            .unwrap_or_else(|_| fail(format!("Unknown strategy: {}", strategy))),
+        None => Strategy::Simulation,
+    };
+
+    if part == Part::One && matches.is_present("stream") {
+        let path = day_input_path(config, 1, matches.value_of("input"))
+            .unwrap_or_else(|e| fail(e));
+
+        let answer = solutions::run_fn(move |_| {
+            one::part_one_streamed(path.as_deref(), width)
+        }, String::new(), config.timeout).unwrap_or_else(|e| fail(e));
+        let answer = answered(answer);
+
+        print_answer(config.format, 1, part, &answer);
+        return;
+    }
+
+    if part == Part::One {
+        let answer = answered(solve_with(config, 1, matches.value_of("input"), move |input| {
+            one::part_one_with(input, width)
+        }));
+
+        print_answer(config.format, 1, part, &answer);
+        return;
+    }
+
+    if !matches.is_present("verbose") {
+        let answer = answered(solve_with(config, 1, matches.value_of("input"), move |input| {
+            one::part_two_with(input, strategy, width)
+        }));
+
+        print_answer(config.format, 1, part, &answer);
+        return;
+    }
+
+    let report = answered(solve_with(config, 1, matches.value_of("input"), move |input| {
+        one::part_two_report(input, strategy, width)
+    }));
+    let answer = Answer { answer: report.answer.frequency.to_string(), elapsed: report.elapsed };
+
+    match config.format {
+        OutputFormat::Text => {
+            println!("{}", answer.answer);
+            print!("{}", report.answer);
+        }
+        OutputFormat::Json => {
+            let output = json!({
+                "day": 1,
+                "part": part.to_string(),
+                "answer": answer.answer,
+                "seconds": duration_secs(&answer),
+                "report": serde_json::to_value(report.answer).unwrap_or_else(|e| {
+                    fail(format!("Could not write the report as JSON: {}", e))
+                }),
+            });
+
+            println!("{}", output);
+        }
+    }
+}
+
+fn run_day1_query(config: &Config, matches: &ArgMatches) {
+    let part = matches.value_of("part").unwrap();
+    let width = day1_width(matches);
+    let value = matches.value_of("value")
+        .unwrap_or_else(|| fail(format!("The {} part needs a value", part)));
+
+    let query = match part {
+        "times" => value.parse().ok().map(Query::ReachedTimes),
+        "repeats" => value.parse().ok().map(Query::Repeats),
+        "visits" => value.parse().ok().map(Query::Visits),
+        _ => value.parse().ok().map(Query::Reach),
+    };
+    let query = query.unwrap_or_else(|| fail(format!("Invalid value: {}", value)));
+
+    let answer = answered(solve_with(config, 1, matches.value_of("input"), move |input| {
+        one::part_query(input, query, width)
+    }));
+
+    print_answer(config.format, 1, part, &answer);
+}
+
+fn run_day1_report(config: &Config, matches: &ArgMatches) {
+    let width = day1_width(matches);
+
+    let report = answered(solve_with(config, 1, matches.value_of("input"), move |input| {
+        one::part_drift_report(input, width)
+    }));
+
+    match config.format {
+        OutputFormat::Text => {
+            print!("{}", report.answer);
+
+            let chart = report.answer.chart(terminal_columns(), CHART_ROWS);
+            if !chart.is_empty() {
+                println!();
+                print!("{}", chart);
+            }
+        }
+        OutputFormat::Json => {
+            let report = serde_json::to_value(report.answer).unwrap_or_else(|e| {
+                fail(format!("Could not write the report as JSON: {}", e))
+            });
+
+            println!("{}", report);
+        }
+    }
+}
+
+/// How many edits the what-if analysis shows when no count is given.
+const WHAT_IF_EDITS: usize = 10;
+
+fn run_day1_what_if(config: &Config, matches: &ArgMatches) {
+    let width = day1_width(matches);
+    let count = match matches.value_of("value") {
+        Some(value) => value.parse()
+            .unwrap_or_else(|_| fail(format!("Invalid value: {}", value))),
+        None => WHAT_IF_EDITS,
+    };
+
+    let mut report = answered(solve_with(config, 1, matches.value_of("input"), move |input| {
+        one::part_what_if(input, width)
+    }));
+    report.answer.edits.truncate(count);
 
-        match part {
-            "one" => { advent_of_code_2018::one::part_one(); }
-            "two" => { advent_of_code_2018::one::part_two(); }
-            p => { println!("Unknown part: {}", p); }
+    match config.format {
+        OutputFormat::Text => { print!("{}", report.answer); }
+        OutputFormat::Json => {
+            let report = serde_json::to_value(report.answer).unwrap_or_else(|e| {
+                fail(format!("Could not write the report as JSON: {}", e))
+            });
+
+            println!("{}", report);
         }
-    } else if let Some(matches) = matches.subcommand_matches("day2") {
-        let part = matches.value_of("part").unwrap();
+    }
+}
+
+fn run_day1_merge(config: &Config, matches: &ArgMatches) {
+    let width = day1_width(matches);
+    let merge = match matches.value_of("merge") {
+        Some(merge) => merge.parse()
+            .unwrap_or_else(|_| fail(format!("Unknown merge policy: {}", merge))),
+        None => Merge::RoundRobin,
+    };
+    let inputs = matches.values_of("value")
+        .unwrap_or_else(|| fail("The merge part needs the input files of the devices"))
+        .map(|path| fs::read_to_string(path)
+            .unwrap_or_else(|e| fail(format!("Could not read {}: {}", path, e))))
+        .collect::<Vec<_>>();
+
+    let report = solutions::run_fn(move |_| one::merge_report(&inputs, merge, width), String::new(), config.timeout)
+        .unwrap_or_else(|e| fail(e));
+    let report = report.answer.unwrap_or_else(|e| fail(e));
+
+    match config.format {
+        OutputFormat::Text => { print!("{}", report); }
+        OutputFormat::Json => { println!("{}", serde_json::to_string(&report).unwrap()); }
+    }
+}
+
+fn run_day2_pairs(config: &Config, matches: &ArgMatches) {
+    let metric = day2_metric(matches);
+    let value = matches.value_of("value")
+        .unwrap_or_else(|| fail("The pairs part needs a distance"));
+    let k: usize = value.parse()
+        .unwrap_or_else(|_| fail(format!("Invalid value: {}", value)));
+
+    let answer = solve_with(config, 2, matches.value_of("input"), move |input| {
+        let box_ids = two::read_input(input);
+
+        two::find_pairs_within(&box_ids, k, metric)
+            .into_iter()
+            .map(|pair| (pair.first.to_string(), pair.second.to_string(), pair.distance, pair.to_string()))
+            .collect::<Vec<_>>()
+    });
+
+    match config.format {
+        OutputFormat::Text => {
+            for (_, _, distance, diff) in &answer.answer {
+                println!("Distance {}:", distance);
+                println!("{}", diff);
+            }
+        }
+        OutputFormat::Json => {
+            let pairs: Vec<_> = answer.answer.iter()
+                .map(|&(ref first, ref second, distance, ref diff)| json!({
+                    "first": first,
+                    "second": second,
+                    "distance": distance,
+                    "diff": diff.lines().collect::<Vec<_>>(),
+                }))
+                .collect();
+
+            println!("{}", json!({
+                "day": 2,
+                "part": "pairs",
+                "pairs": pairs,
+                "seconds": duration_secs(&answer),
+            }));
+        }
+    }
+}
+
+/// How many IDs the nearest part lists when not given a count or a radius.
+const NEAREST_IDS: usize = 5;
+
+fn run_day2_nearest(config: &Config, matches: &ArgMatches) {
+    let metric = day2_metric(matches);
+    let id = matches.value_of("value")
+        .unwrap_or_else(|| fail("The nearest part needs an ID"))
+        .to_string();
+    let parse_number = |name| matches.value_of(name).map(|value: &str| {
+        value.parse::<usize>().unwrap_or_else(|_| fail(format!("Invalid {}: {}", name, value)))
+    });
+    let radius = parse_number("radius");
+    let count = parse_number("count").unwrap_or(NEAREST_IDS);
+
+    let answer = solve_with(config, 2, matches.value_of("input"), move |input| {
+        let mut tree = BkTree::new(metric);
+        for box_id in two::read_input(input).iter() {
+            tree.insert(box_id);
+        }
+
+        let found = match radius {
+            Some(radius) => tree.within(&id, radius),
+            None => tree.nearest(&id, count),
+        };
+
+        found.into_iter()
+            .map(|(distance, box_id)| (distance, box_id.to_string()))
+            .collect::<Vec<_>>()
+    });
 
-        match part {
-            "one" => { advent_of_code_2018::two::part_one(); }
-            "two" => { advent_of_code_2018::two::part_two(); }
-            p => { println!("Unknown part: {}", p); }
+    match config.format {
+        OutputFormat::Text => {
+            for &(distance, ref box_id) in &answer.answer {
+                println!("{:>3}  {}", distance, box_id);
+            }
         }
-    } else if let Some(matches) = matches.subcommand_matches("day3") {
-        let part = matches.value_of("part").unwrap();
+        OutputFormat::Json => {
+            let found: Vec<_> = answer.answer.iter()
+                .map(|&(distance, ref box_id)| json!({ "id": box_id, "distance": distance }))
+                .collect();
 
-        match part {
-            "one" => { advent_of_code_2018::three::part_one(); }
-            "two" => { advent_of_code_2018::three::part_two(); }
-            p => { println!("Unknown part: {}", p); }
+            println!("{}", json!({
+                "day": 2,
+                "part": "nearest",
+                "ids": found,
+                "seconds": duration_secs(&answer),
+            }));
         }
     }
 }
+
+fn day2_metric(matches: &ArgMatches) -> Metric {
+    match matches.value_of("metric") {
+        Some(metric) => metric.parse()
+            .unwrap_or_else(|_| fail(format!("Unknown metric: {}", metric))),
+        None => Metric::Levenshtein,
+    }
+}
+
+/// The height of the charts drawn in reports.
+const CHART_ROWS: usize = 15;
+
+/// Returns the width of the terminal. `COLUMNS` overrides the size of the
+/// terminal that stdout is connected to, and the width is 80 when stdout is
+/// not a terminal and `COLUMNS` is not set.
+fn terminal_columns() -> usize {
+    env::var("COLUMNS").ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns| columns > 0)
+        .or_else(|| terminal_size::terminal_size().map(|(width, _)| usize::from(width.0)))
+        .unwrap_or(80)
+}
+
+fn day1_width(matches: &ArgMatches) -> Width {
+    match matches.value_of("width") {
+        Some(width) => width.parse()
+            .unwrap_or_else(|_| fail(format!("Unknown width: {}", width))),
+        None => Width::I32,
+    }
+}
+
+/// Runs the given solution on its input, exiting if it fails.
+fn solve(config: &Config, solution: &Solution, input: Option<&str>) -> Answer {
+    answered(solve_with(config, solution.day, input, solution.solve))
+}
+
+/// Runs the given function on the input for the given day, exiting if it
+/// fails.
+fn solve_with<F, T>(config: &Config, day: u32, input: Option<&str>, solve: F) -> Answer<T>
+    where F: FnOnce(&str) -> T + Send + 'static, T: Send + 'static {
+    let input = read_day_input(config, day, input)
+        .unwrap_or_else(|e| fail(e));
+
+    solutions::run_fn(solve, input, config.timeout).unwrap_or_else(|e| fail(e))
+}
+
+/// Unwraps an answer that may have failed, exiting with its error if it did.
+fn answered<T, E: Display>(answer: Answer<Result<T, E>>) -> Answer<T> {
+    Answer {
+        answer: answer.answer.unwrap_or_else(|e| fail(e)),
+        elapsed: answer.elapsed,
+    }
+}
+
+fn run_fetch(config: &Config, matches: &ArgMatches) {
+    let day = parse_day(matches.value_of("day").unwrap());
+
+    match fetch::fetch_input(config, day, || Client::from_config(config)) {
+        Ok(Fetched::Cached(path)) => { println!("Using cached {}", path.display()); }
+        Ok(Fetched::Downloaded(path)) => { println!("Downloaded {}", path.display()); }
+        Err(e) => { fail(e); }
+    }
+}
+
+fn run_submit(config: &Config, matches: &ArgMatches) {
+    let day = parse_day(matches.value_of("day").unwrap());
+    let part = matches.value_of("part").unwrap();
+
+    let solution = match part.parse().ok().and_then(|p| solutions::find(day, p)) {
+        Some(solution) => solution,
+        None => fail(format!("No solution for day {} part {}", day, part)),
+    };
+
+    // An answer worked out from whatever happens to be on stdin is too
+    // easily wrong, and a wrong answer cannot be submitted again
+    if matches.value_of("input") == Some("-") {
+        fail("Answers can only be submitted from an input file, not from stdin");
+    }
+
+    let answer = solve(config, solution, matches.value_of("input"));
+    let log = SubmissionLog::new(&config.cache_dir);
+
+    let outcome = submit::submit(&log, day, solution.part, &answer.answer, || {
+        Client::from_config(config)
+    }).unwrap_or_else(|e| fail(e));
+
+    match config.format {
+        OutputFormat::Text => { println!("{}: {}", answer.answer, outcome); }
+        OutputFormat::Json => {
+            let output = json!({
+                "day": day,
+                "part": solution.part.to_string(),
+                "answer": answer.answer,
+                "outcome": outcome.to_string(),
+            });
+
+            println!("{}", output);
+        }
+    }
+}
+
+fn run_leaderboard(config: &Config, matches: &ArgMatches) {
+    let path = matches.value_of("file").unwrap();
+
+    let json = fs::read_to_string(path).unwrap_or_else(|e| fail(e));
+    let leaderboard = leaderboard::parse(&json)
+        .unwrap_or_else(|e| fail(format!("Could not parse {}: {}", path, e)));
+
+    let report = leaderboard::report(&leaderboard);
+
+    match config.format {
+        OutputFormat::Text => { print!("{}", report); }
+        OutputFormat::Json => { println!("{}", serde_json::to_string_pretty(&report).unwrap()); }
+    }
+}
+
+fn run_report(config: &Config, matches: &ArgMatches) {
+    let path = matches.value_of("readme").unwrap();
+
+    let entries = report::run_all(config);
+    let section = report::render(&entries);
+
+    let readme = fs::read_to_string(path).unwrap_or_else(|e| fail(e));
+    let updated = report::replace_section(&readme, &section)
+        .unwrap_or_else(|e| fail(format!("{}: {}", path, e)));
+    fs::write(path, updated).unwrap_or_else(|e| fail(e));
+
+    match config.format {
+        OutputFormat::Text => { print!("{}", section); }
+        OutputFormat::Json => { println!("{}", serde_json::to_string_pretty(&entries).unwrap()); }
+    }
+}
+
+fn parse_day(day: &str) -> u32 {
+    match day.trim_start_matches("day").parse() {
+        Ok(day) if (1..=25).contains(&day) => day,
+        _ => fail(format!("Invalid day: {}", day)),
+    }
+}
+
+/// Reads the input for the given day from the given file, or from stdin for
+/// `-`, or else from the configured input file.
+fn read_day_input(config: &Config, day: u32, path: Option<&str>) -> io::Result<String> {
+    match day_input_path(config, day, path)? {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+
+            Ok(input)
+        }
+    }
+}
+
+/// Works out which file to read the input for a day from, or `None` for
+/// stdin, which is only read when asked for with `-`. Without a path, the
+/// configured input file must exist.
+fn day_input_path(config: &Config, day: u32, path: Option<&str>) -> io::Result<Option<PathBuf>> {
+    match path {
+        Some("-") => Ok(None),
+        Some(path) => Ok(Some(PathBuf::from(path))),
+        None => {
+            let path = config.input_path(day);
+            if !path.is_file() {
+                return Err(io::Error::new(io::ErrorKind::NotFound, format!(
+                    "No input file at {}, give one with --input, or --input - to read stdin",
+                    path.display()
+                )));
+            }
+
+            Ok(Some(path))
+        }
+    }
+}
+
+fn print_answer<P: Display>(format: OutputFormat, day: u32, part: P, answer: &Answer) {
+    match format {
+        OutputFormat::Text => { println!("{}", answer.answer); }
+        OutputFormat::Json => {
+            let output = json!({
+                "day": day,
+                "part": part.to_string(),
+                "answer": answer.answer,
+                "seconds": duration_secs(answer),
+            });
+
+            println!("{}", output);
+        }
+    }
+}
+
+fn duration_secs<T>(answer: &Answer<T>) -> f64 {
+    answer.elapsed.as_secs() as f64 + f64::from(answer.elapsed.subsec_nanos()) * 1e-9
+}
+
+fn fail<E: Display>(err: E) -> ! {
+    eprintln!("{}", err);
+    process::exit(1);
+}
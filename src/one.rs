@@ -1,131 +1,3284 @@
+use std::cmp::Reverse;
 use std::collections::LinkedList;
-use std::collections::HashSet;
-use std::io;
-use std::io::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufRead};
+use std::ops::{Add, Sub};
+use std::panic;
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
 
-pub fn part_one() {
-    let changes = read_input();
-    let answer = sum_changes(&changes);
+use memmap2::Mmap;
 
-    println!("{}", answer);
+/// An integer type that frequencies can be measured in.
+pub trait Frequency: Copy + Eq + Ord + Hash + fmt::Debug + fmt::Display
+    + Add<Output = Self> + Sub<Output = Self> + Delta<Frequency = Self> {
+    fn zero() -> Self;
+
+    fn to_i128(self) -> i128;
+
+    /// Converts from the widest frequency type, returning `None` if the
+    /// value does not fit.
+    fn from_i128(value: i128) -> Option<Self>;
+
+    /// Adds the given change, returning `None` if the result does not fit.
+    fn checked_add(self, change: Self) -> Option<Self>;
+
+    /// Takes away the given change, returning `None` if the result does not
+    /// fit.
+    fn checked_sub(self, change: Self) -> Option<Self>;
+
+    /// Multiplies by the given factor, returning `None` if the result does
+    /// not fit.
+    fn checked_mul(self, factor: Self) -> Option<Self>;
+
+    /// Returns the non-negative remainder of dividing by the given modulus,
+    /// or `None` if the modulus is zero or the result does not fit.
+    fn checked_modulo(self, modulus: Self) -> Option<Self>;
+
+    /// Returns the non-negative remainder of dividing by the given modulus.
+    fn modulo(self, modulus: Self) -> Self;
+}
+
+/// A single frequency change, either as an integer or as a reference to one,
+/// so that the day 1 functions can take any collection of changes.
+pub trait Delta {
+    type Frequency: Frequency;
+
+    fn value(self) -> Self::Frequency;
+}
+
+macro_rules! impl_frequency {
+    ($($t:ty),*) => {
+        $(
+            impl Frequency for $t {
+                fn zero() -> $t { 0 }
+
+                fn to_i128(self) -> i128 { i128::from(self) }
+
+                fn from_i128(value: i128) -> Option<$t> { <$t>::try_from(value).ok() }
+
+                fn checked_add(self, change: $t) -> Option<$t> { <$t>::checked_add(self, change) }
+
+                fn checked_sub(self, change: $t) -> Option<$t> { <$t>::checked_sub(self, change) }
+
+                fn checked_mul(self, factor: $t) -> Option<$t> { <$t>::checked_mul(self, factor) }
+
+                fn checked_modulo(self, modulus: $t) -> Option<$t> { self.checked_rem_euclid(modulus) }
+
+                fn modulo(self, modulus: $t) -> $t { self.wrapping_rem_euclid(modulus) }
+            }
+
+            impl Delta for $t {
+                type Frequency = $t;
+
+                fn value(self) -> $t { self }
+            }
+
+            impl<'a> Delta for &'a $t {
+                type Frequency = $t;
+
+                fn value(self) -> $t { *self }
+            }
+        )*
+    }
+}
+
+impl_frequency!(i32, i64, i128);
+
+pub fn part_one(input: &str) -> Result<String, PartError> {
+    part_one_with(input, Width::I32)
+}
+
+/// Runs part one with frequencies measured in the given integer type. The
+/// input may use any of the operations of `Change`.
+pub fn part_one_with(input: &str, width: Width) -> Result<String, PartError> {
+    fn total<T: Frequency + FromStr>(input: &str) -> Result<String, PartError> {
+        let changes: Vec<Change<T>> = parse_changes(input)?;
+
+        Ok(evaluate(&changes)?.to_string())
+    }
+
+    match width {
+        Width::I32 => total::<i32>(input),
+        Width::I64 => total::<i64>(input),
+        Width::I128 => total::<i128>(input),
+    }
+}
+
+/// Runs part one without reading the whole input into memory, with
+/// frequencies measured in the given integer type. A file is memory-mapped
+/// and evaluated in parallel, while stdin is evaluated as it is read. The
+/// input may use any of the operations of `Change`.
+pub fn part_one_streamed(path: Option<&Path>, width: Width) -> Result<String, SumError> {
+    fn total<T: Frequency + FromStr + Send>(path: Option<&Path>) -> Result<String, SumError> {
+        let frequency: T = match path {
+            Some(path) => {
+                let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+                evaluate_file_parallel(path, threads)?
+            }
+            None => evaluate_reader(io::stdin().lock())?,
+        };
+
+        Ok(frequency.to_string())
+    }
+
+    match width {
+        Width::I32 => total::<i32>(path),
+        Width::I64 => total::<i64>(path),
+        Width::I128 => total::<i128>(path),
+    }
+}
+
+pub fn part_two(input: &str) -> Result<String, PartError> {
+    part_two_with(input, Strategy::Simulation, Width::I32)
+}
+
+/// Runs part two using the given strategy to search for the repeat, with
+/// frequencies measured in the given integer type.
+pub fn part_two_with(input: &str, strategy: Strategy, width: Width) -> Result<String, PartError> {
+    part_two_report(input, strategy, width).map(|report| report.frequency.to_string())
+}
+
+/// Runs part two in the same way as `part_two_with`, returning the full
+/// details of where the repeat happens rather than just the answer. The
+/// input may use any of the operations of `Change`, in which case the
+/// strategy only applies if every change adds to the frequency.
+pub fn part_two_report(input: &str, strategy: Strategy, width: Width) -> Result<RepeatReport<i128>, PartError> {
+    fn report<T: Frequency + FromStr>(input: &str, strategy: Strategy) -> Result<RepeatReport<i128>, PartError> {
+        let changes: Vec<Change<T>> = parse_changes(input)?;
+
+        Ok(program_repeat_report(&changes, strategy)?.widen())
+    }
+
+    match width {
+        Width::I32 => report::<i32>(input, strategy),
+        Width::I64 => report::<i64>(input, strategy),
+        Width::I128 => report::<i128>(input, strategy),
+    }
+}
+
+/// The extra questions about the frequencies that can be answered from the
+/// command line, beyond the two parts of the puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Query {
+    /// The first frequency reached the given number of times.
+    ReachedTimes(usize),
+    /// The given number of frequencies that are reached twice first.
+    Repeats(usize),
+    /// How many times each frequency is reached within the given number of
+    /// passes.
+    Visits(u128),
+    /// When the given frequency is first reached.
+    Reach(i128),
+}
+
+/// Answers the given query about the frequencies, with frequencies measured
+/// in the given integer type. Answers with several values have one per line.
+///
+/// Only additions are supported, as the answers rely on the frequency
+/// drifting by the same amount on each pass.
+pub fn part_query(input: &str, query: Query, width: Width) -> Result<String, PartError> {
+    fn answer<T: Frequency + FromStr>(input: &str, query: Query) -> Result<String, PartError> {
+        let changes: Vec<T> = parse_additions(input)?;
+
+        let lines: Vec<String> = match query {
+            Query::ReachedTimes(times) => vec![first_reached_times(changes, times)?.to_string()],
+            Query::Repeats(count) => {
+                first_repeats(changes, count)?
+                    .iter()
+                    .map(|frequency| frequency.to_string())
+                    .collect()
+            }
+            Query::Visits(passes) => {
+                visit_counts(changes, passes)?
+                    .iter()
+                    .map(|(frequency, count)| format!("{}: {}", frequency, count))
+                    .collect()
+            }
+            Query::Reach(target) => {
+                let reached = match T::from_i128(target) {
+                    Some(target) => first_time_reaching(changes, target)?,
+                    None => None,
+                };
+
+                match reached {
+                    Some((pass, index)) => vec![format!("pass {}, change {}", pass, index)],
+                    None => vec!["never".to_string()],
+                }
+            }
+        };
+
+        Ok(lines.join("\n"))
+    }
+
+    match width {
+        Width::I32 => answer::<i32>(input, query),
+        Width::I64 => answer::<i64>(input, query),
+        Width::I128 => answer::<i128>(input, query),
+    }
+}
+
+/// Works out the effect of negating or removing each change, with
+/// frequencies measured in the given integer type. Only additions are
+/// supported.
+pub fn part_what_if(input: &str, width: Width) -> Result<WhatIfReport<i128>, PartError> {
+    fn report<T: Frequency + FromStr>(input: &str) -> Result<WhatIfReport<i128>, PartError> {
+        let changes: Vec<T> = parse_additions(input)?;
+
+        Ok(what_if(&changes)?.widen())
+    }
+
+    match width {
+        Width::I32 => report::<i32>(input),
+        Width::I64 => report::<i64>(input),
+        Width::I128 => report::<i128>(input),
+    }
+}
+
+/// Works out how the frequency drifts, with frequencies measured in the
+/// given integer type. Only additions are supported.
+pub fn part_drift_report(input: &str, width: Width) -> Result<DriftReport<i128>, PartError> {
+    fn report<T: Frequency + FromStr>(input: &str) -> Result<DriftReport<i128>, PartError> {
+        let changes: Vec<T> = parse_additions(input)?;
+
+        Ok(drift_report(changes)?.widen())
+    }
+
+    match width {
+        Width::I32 => report::<i32>(input),
+        Width::I64 => report::<i64>(input),
+        Width::I128 => report::<i128>(input),
+    }
 }
 
-pub fn part_two() {
-    let changes = read_input();
-    let answer = get_first_repeat_frequency(&changes);
+/// The integer types that frequencies can be measured in when solving the
+/// puzzle. Wider types can handle larger inputs without overflowing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Width {
+    I32,
+    I64,
+    I128,
+}
+
+impl FromStr for Width {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Width, ()> {
+        match s {
+            "i32" => Ok(Width::I32),
+            "i64" => Ok(Width::I64),
+            "i128" => Ok(Width::I128),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Reads in the input lines and converts them to a LinkedList of i32 values,
+/// or returns an error if the input is malformed, see `parse_changes`.
+pub fn read_input(input: &str) -> Result<LinkedList<i32>, ParseError> {
+    parse_changes(input).map(|changes| changes.into_iter().collect())
+}
 
-    println!("{}", answer);
+/// Something that stopped a part of the puzzle from being answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartError {
+    Parse(ParseError),
+    Overflow(Overflow),
+    /// No frequency is ever reached twice.
+    NoRepeat,
+    /// The change at the given index, counting from zero, does not add to
+    /// the frequency, which the part needs all of them to do.
+    Unsupported { index: usize, change: String },
 }
 
-/// Reads in the input lines and converts them to a LinkedList of i32 values.
-pub fn read_input() -> LinkedList<i32> {
-    let stdin = io::stdin();
+impl fmt::Display for PartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PartError::Parse(ref err) => write!(f, "{}", err),
+            PartError::Overflow(ref overflow) => write!(f, "{}", overflow),
+            PartError::NoRepeat => write!(f, "{}", RepeatError::NoRepeat),
+            PartError::Unsupported { index, ref change } => {
+                write!(f, "Change {} ({}) is not supported in this mode, which only handles additions", index, change)
+            }
+        }
+    }
+}
+
+impl Error for PartError {}
+
+impl From<ParseError> for PartError {
+    fn from(err: ParseError) -> PartError {
+        PartError::Parse(err)
+    }
+}
 
-    let mut changes: LinkedList<i32> = LinkedList::new();
-    for line in stdin.lock().lines() {
-        let change: i32 = line.unwrap().parse().unwrap();
+impl From<Overflow> for PartError {
+    fn from(overflow: Overflow) -> PartError {
+        PartError::Overflow(overflow)
+    }
+}
 
-        changes.push_back(change);
+impl From<RepeatError> for PartError {
+    fn from(err: RepeatError) -> PartError {
+        match err {
+            RepeatError::NoRepeat => PartError::NoRepeat,
+            RepeatError::Overflow(overflow) => PartError::Overflow(overflow),
+        }
     }
+}
 
-    changes
+/// A token in the input that is not a valid frequency change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line of the token, counting from one.
+    pub line: usize,
+    /// The column of the start of the token, in characters, counting from
+    /// one.
+    pub column: usize,
+    pub token: String,
 }
 
-/// Sums up the provided changes and returns the resulting frequency.
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid change {:?} at line {}, column {}", self.token, self.line, self.column)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses frequency changes separated by newlines, commas or both, as in the
+/// puzzle's example form `+1, -2, +3, +1`.
+///
+/// Anything after a `#` on a line is a comment, and a line may end with a
+/// comma. Lines that are blank once comments are removed are skipped. Both
+/// `\n` and `\r\n` line endings are accepted.
 ///
 /// ```
-/// use std::collections::LinkedList;
 /// use advent_of_code_2018::one;
 ///
-/// let mut changes = LinkedList::new();
+/// let input = "+1, -2\r\n# Calibration\r\n+3\r\n+1 # Last one\r\n\r\n";
 ///
-/// changes.push_back(2);
-/// changes.push_back(1);
-/// changes.push_back(-4);
-/// changes.push_back(5);
+/// assert_eq!(one::parse_changes::<i32>(input), Ok(vec![1, -2, 3, 1]));
+/// ```
+pub fn parse_changes<T: FromStr>(input: &str) -> Result<Vec<T>, ParseError> {
+    let mut changes = Vec::new();
+
+    for (line_index, line) in input.lines().enumerate() {
+        parse_line(line, line_index, |change| {
+            changes.push(change);
+            Ok::<(), ParseError>(())
+        })?;
+    }
+
+    Ok(changes)
+}
+
+/// Parses the changes on one line of the input, as described for
+/// `parse_changes`, passing each of them to the given function in turn.
+fn parse_line<T, F, E>(line: &str, line_index: usize, mut emit: F) -> Result<(), E>
+    where T: FromStr, F: FnMut(T) -> Result<(), E>, E: From<ParseError> {
+    let content = match line.find('#') {
+        Some(end) => &line[..end],
+        None => line,
+    };
+
+    // A trailing comma may join the line onto the next one
+    let content = content.trim_end();
+    let content = content.strip_suffix(',').unwrap_or(content);
+    if content.trim().is_empty() {
+        return Ok(());
+    }
+
+    let mut field_start = 0;
+    for field in content.split(',') {
+        let token = field.trim();
+        let token_start = field_start + field.len() - field.trim_start().len();
+        field_start += field.len() + 1;
+
+        match token.parse() {
+            Ok(change) => emit(change)?,
+            Err(_) => {
+                return Err(E::from(ParseError {
+                    line: line_index + 1,
+                    column: line[..token_start].chars().count() + 1,
+                    token: token.to_string(),
+                }));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sums up the given changes and returns the resulting frequency.
 ///
-/// let res = one::sum_changes(&changes);
+/// The changes can be given as any collection or iterator of integers, or of
+/// references to integers. It panics if the frequency overflows, see
+/// `checked_sum` for inputs that may do so.
 ///
-/// assert_eq!(res, 4);
 /// ```
-pub fn sum_changes(changes: &LinkedList<i32>) -> i32 {
-    changes.iter().sum()
+/// use advent_of_code_2018::one;
+///
+/// assert_eq!(one::sum(&[2, 1, -4, 5]), 4);
+/// assert_eq!(one::sum(vec![2i64, 1, -4, 5]), 4);
+/// ```
+pub fn sum<I>(changes: I) -> <I::Item as Delta>::Frequency
+    where I: IntoIterator, I::Item: Delta {
+    checked_sum(changes).unwrap_or_else(|overflow| panic!("{}", overflow))
 }
 
-/// Repeatedly re-sums up the given changes and returns the first repeated
-/// frequency.
+/// Sums up the given changes, or returns an error giving the index of the
+/// change at which the frequency no longer fits in its type.
 ///
-/// If no repeats ever occur, then this function will not halt.
+/// ```
+/// use advent_of_code_2018::one::{self, Overflow};
 ///
+/// assert_eq!(one::checked_sum(&[2, 1, -4, 5]), Ok(4));
+/// assert_eq!(one::checked_sum(&[1, i32::MAX, -5]), Err(Overflow { pass: 0, index: 1 }));
+/// assert_eq!(one::checked_sum(&[1i64, i64::from(i32::MAX), -5]), Ok(2147483643));
 /// ```
-/// use std::collections::LinkedList;
-/// use advent_of_code_2018::one;
+pub fn checked_sum<I>(changes: I) -> Result<<I::Item as Delta>::Frequency, Overflow>
+    where I: IntoIterator, I::Item: Delta {
+    checked_prefix_sums(changes).map(|sums| sums.last().cloned().unwrap_or_else(Frequency::zero))
+}
+
+/// A frequency that did not fit in its integer type, and where it was
+/// reached. Passes and indices count from zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+    pub pass: u128,
+    pub index: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "The frequency overflowed at change {} of pass {}, try a wider integer type",
+            self.index, self.pass
+        )
+    }
+}
+
+impl Error for Overflow {}
+
+/// Something that went wrong while applying changes read from a file or a
+/// stream.
+#[derive(Debug)]
+pub enum SumError {
+    Io(io::Error),
+    Parse(ParseError),
+    Overflow(Overflow),
+}
+
+impl fmt::Display for SumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SumError::Io(ref err) => write!(f, "{}", err),
+            SumError::Parse(ref err) => write!(f, "{}", err),
+            SumError::Overflow(ref overflow) => write!(f, "{}", overflow),
+        }
+    }
+}
+
+impl Error for SumError {}
+
+impl From<io::Error> for SumError {
+    fn from(err: io::Error) -> SumError {
+        SumError::Io(err)
+    }
+}
+
+impl From<ParseError> for SumError {
+    fn from(err: ParseError) -> SumError {
+        SumError::Parse(err)
+    }
+}
+
+impl From<Overflow> for SumError {
+    fn from(overflow: Overflow) -> SumError {
+        SumError::Overflow(overflow)
+    }
+}
+
+/// Applies the changes read from the given reader in the same way as
+/// `evaluate`, in any of the forms that `parse_changes` accepts, without
+/// keeping them in memory.
 ///
-/// let mut changes = LinkedList::new();
+/// Only one line of the input is held at a time, so an input of any size
+/// can be evaluated in constant memory as long as its lines are short.
 ///
-/// changes.push_back(2);
-/// changes.push_back(1);
-/// changes.push_back(-1);
-/// changes.push_back(5);
+/// ```
+/// use std::io::Cursor;
 ///
-/// let res = one::get_first_repeat_frequency(&changes);
+/// use advent_of_code_2018::one;
 ///
-/// assert_eq!(res, 2);
+/// let input = Cursor::new("+1, -2\r\n# Calibration\r\n+3\r\n+1\r\n");
+///
+/// assert_eq!(one::evaluate_reader::<i32, _>(input).unwrap(), 3);
+/// assert_eq!(one::evaluate_reader::<i32, _>(Cursor::new("+5\n*3\n%4\n-3\n")).unwrap(), 0);
 /// ```
-pub fn get_first_repeat_frequency(changes: &LinkedList<i32>) -> i32 {
-    let mut frequencies: HashSet<i32> = HashSet::new();
-    let mut sum = 0;
+pub fn evaluate_reader<T, R>(reader: R) -> Result<T, SumError>
+    where T: Frequency + FromStr, R: BufRead {
+    evaluate_chunk(reader).map(|chunk| chunk.frequency)
+}
+
+/// The frequency reached by applying a chunk of the changes starting from
+/// zero, along with the lowest and highest frequencies reached within it.
+/// The bounds only mean anything if every change in the chunk is additive,
+/// as only then does the chunk move by the frequency before it.
+struct Chunk<T> {
+    frequency: T,
+    low: T,
+    high: T,
+    additive: bool,
+}
 
-    loop {
-        for change in changes.iter() {
-            sum += change;
+fn evaluate_chunk<T, R>(mut reader: R) -> Result<Chunk<T>, SumError>
+    where T: Frequency + FromStr, R: BufRead {
+    let zero = T::zero();
+    let mut chunk = Chunk { frequency: zero, low: zero, high: zero, additive: true };
+    let mut index = 0;
 
-            if frequencies.contains(&sum) {
-                return sum;
-            } else {
-                frequencies.insert(sum);
-            }
+    let mut line = String::new();
+    for line_index in 0.. {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
         }
+
+        let content = line.strip_suffix('\n').unwrap_or(&line);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+
+        parse_line(content, line_index, |change: Change<T>| {
+            chunk.frequency = change.apply(chunk.frequency).ok_or(Overflow { pass: 0, index })?;
+            chunk.low = chunk.low.min(chunk.frequency);
+            chunk.high = chunk.high.max(chunk.frequency);
+            chunk.additive &= matches!(change, Change::Add(_));
+            index += 1;
+
+            Ok::<(), SumError>(())
+        })?;
     }
+
+    Ok(chunk)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Applies the changes in the file at the given path in the same way as
+/// `evaluate_reader`, but memory-maps the file and evaluates chunks of its
+/// lines in parallel on the given number of threads.
+///
+/// The chunks are added up in order, checking that the frequencies of each
+/// chunk still fit once moved by the chunks before it. Only chunks that
+/// just add to the frequency can be moved like this. If a chunk has any
+/// other operation, does not fit or could not be evaluated, the whole file
+/// is evaluated again in order, so that the result or the error is the same
+/// one that `evaluate_reader` would give.
+pub fn evaluate_file_parallel<T>(path: &Path, threads: usize) -> Result<T, SumError>
+    where T: Frequency + FromStr + Send {
+    let file = File::open(path)?;
 
-    use std::collections::LinkedList;
+    // Empty files cannot be mapped
+    if file.metadata()?.len() == 0 {
+        return Ok(T::zero());
+    }
 
-    #[test]
-    fn sum_changes_it_sums_zero_changes() {
-        let changes = LinkedList::new();
-        let res = sum_changes(&changes);
+    // The map is only valid as long as nothing else changes the file
+    let map = unsafe { Mmap::map(&file)? };
+    let bytes = &map[..];
 
-        assert_eq!(res, 0);
+    let chunks = thread::scope(|scope| {
+        let handles: Vec<_> = split_lines(bytes, threads)
+            .into_iter()
+            .map(|chunk| scope.spawn(move || evaluate_chunk::<T, _>(chunk)))
+            .collect();
+
+        handles.into_iter()
+            .map(|handle| handle.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect::<Vec<_>>()
+    });
+
+    let mut total = T::zero();
+    for chunk in chunks {
+        let moved = chunk.ok().filter(|chunk| chunk.additive).and_then(|chunk| {
+            total.checked_add(chunk.low)?;
+            total.checked_add(chunk.high)?;
+            total.checked_add(chunk.frequency)
+        });
+
+        match moved {
+            Some(frequency) => total = frequency,
+            None => return evaluate_reader(bytes),
+        }
     }
 
-    #[test]
-    fn sum_changes_it_sums_one_change() {
-        let mut changes = LinkedList::new();
+    Ok(total)
+}
 
-        changes.push_back(2);
+/// Splits the bytes into at most the given number of chunks of about the
+/// same size, each made up of whole lines.
+fn split_lines(bytes: &[u8], count: usize) -> Vec<&[u8]> {
+    let count = count.max(1);
+    let mut chunks = Vec::with_capacity(count);
+    let mut start = 0;
 
-        let res = sum_changes(&changes);
+    for i in 1..=count {
+        let target = (bytes.len() / count * i).max(start);
+        let end = match bytes[target..].iter().position(|&b| b == b'\n') {
+            Some(newline) if i < count => target + newline + 1,
+            _ => bytes.len(),
+        };
 
-        assert_eq!(res, 2);
+        if end > start {
+            chunks.push(&bytes[start..end]);
+            start = end;
+        }
     }
 
-    #[test]
-    fn sum_changes_it_sums_multiple_changes() {
-        let mut changes = LinkedList::new();
+    chunks
+}
 
-        changes.push_back(2);
-        changes.push_back(1);
-        changes.push_back(-4);
-        changes.push_back(5);
+/// An endless walk through the frequencies reached by applying the changes
+/// over and over again, starting from zero.
+///
+/// Each step yields the pass and the index of the change within the pass,
+/// both counting from zero, along with the change and the frequency reached
+/// by applying it.
+///
+/// The walk only ends if there are no changes, or if the next frequency would
+/// not fit in its type, in which case `overflow` gives where that happened.
+///
+/// ```
+/// use advent_of_code_2018::one::FrequencyWalk;
+///
+/// let steps: Vec<_> = FrequencyWalk::new(&[1, -2, 3]).take(4).collect();
+///
+/// assert_eq!(steps, vec![(0, 0, 1, 1), (0, 1, -2, -1), (0, 2, 3, 2), (1, 0, 1, 3)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrequencyWalk<T> {
+    changes: Vec<T>,
+    pass: u128,
+    index: usize,
+    frequency: T,
+    overflow: Option<Overflow>,
+}
 
-        let res = sum_changes(&changes);
+impl<T: Frequency> FrequencyWalk<T> {
+    pub fn new<I>(changes: I) -> FrequencyWalk<T>
+        where I: IntoIterator, I::Item: Delta<Frequency = T> {
+        FrequencyWalk {
+            changes: changes.into_iter().map(Delta::value).collect(),
+            pass: 0,
+            index: 0,
+            frequency: T::zero(),
+            overflow: None,
+        }
+    }
 
-        assert_eq!(res, 4);
+    /// Returns where the frequency overflowed, if that is why the walk
+    /// ended.
+    pub fn overflow(&self) -> Option<Overflow> {
+        self.overflow
+    }
+}
+
+impl<T: Frequency> Iterator for FrequencyWalk<T> {
+    type Item = (u128, usize, T, T);
+
+    fn next(&mut self) -> Option<(u128, usize, T, T)> {
+        if self.overflow.is_some() {
+            return None;
+        }
+
+        let (pass, index) = (self.pass, self.index);
+        let change = *self.changes.get(index)?;
+
+        match Frequency::checked_add(self.frequency, change) {
+            Some(frequency) => self.frequency = frequency,
+            None => {
+                self.overflow = Some(Overflow { pass, index });
+
+                return None;
+            }
+        }
+
+        self.index += 1;
+        if self.index == self.changes.len() {
+            self.index = 0;
+            self.pass += 1;
+        }
+
+        Some((pass, index, change, self.frequency))
+    }
+}
+
+/// Repeatedly re-sums up the given changes and returns the first repeated
+/// frequency.
+///
+/// If no repeats ever occur, then this function will not halt. It panics if
+/// there are no changes, or if the frequency overflows before repeating.
+///
+/// ```
+/// use advent_of_code_2018::one;
+///
+/// assert_eq!(one::first_repeat(&[2, 1, -1, 5]), 2);
+/// assert_eq!(one::first_repeat(vec![-1i128, 1, 2]), 2);
+/// ```
+pub fn first_repeat<I>(changes: I) -> <I::Item as Delta>::Frequency
+    where I: IntoIterator, I::Item: Delta {
+    let changes: Vec<_> = changes.into_iter().map(Delta::value).collect();
+    let visited = VisitedSet::for_changes(&changes);
+
+    first_repeat_using(&changes, visited)
+}
+
+/// Returns the first repeated frequency in the same way as `first_repeat`,
+/// remembering the frequencies that have been reached in the given set.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, VisitedSet};
+///
+/// let changes = [7, 7, -2, -7, -4];
+///
+/// assert_eq!(one::first_repeat_using(&changes, VisitedSet::hashed()), 14);
+/// assert_eq!(one::first_repeat_using(&changes, VisitedSet::with_range(0, 20).unwrap()), 14);
+/// ```
+pub fn first_repeat_using<T: Frequency>(changes: &[T], mut visited: VisitedSet<T>) -> T {
+    FrequencyWalk::new(changes.iter().cloned())
+        .map(|(_, _, _, frequency)| frequency)
+        .find(|&frequency| !visited.insert(frequency))
+        .expect("The frequencies ran out before any of them repeated")
+}
+
+/// The most frequencies that `VisitedSet::for_changes` will use a bitset
+/// for, which takes 32 MiB.
+pub const BITSET_LIMIT: u128 = 1 << 28;
+
+/// A set of the frequencies that have been reached.
+///
+/// Frequencies within a given range are kept as bits in a bitset, offset so
+/// that the lowest frequency of the range is the first bit, which is much
+/// faster than hashing them. Any frequencies outside of the range are hashed
+/// instead.
+#[derive(Debug, Clone)]
+pub struct VisitedSet<T> {
+    low: i128,
+    len: u128,
+    bits: Vec<u64>,
+    outside: HashSet<T>,
+}
+
+impl<T: Frequency> VisitedSet<T> {
+    /// Creates a set that hashes every frequency.
+    pub fn hashed() -> VisitedSet<T> {
+        VisitedSet { low: 0, len: 0, bits: Vec::new(), outside: HashSet::new() }
+    }
+
+    /// Creates a set with a bit for each frequency from `low` to `high`,
+    /// inclusive, or returns `None` if `low` is above `high` or the range
+    /// has more than `BITSET_LIMIT` frequencies.
+    pub fn with_range(low: T, high: T) -> Option<VisitedSet<T>> {
+        let (low, high) = (low.to_i128(), high.to_i128());
+        if low > high {
+            return None;
+        }
+
+        match high.checked_sub(low)? as u128 {
+            width if width < BITSET_LIMIT => Some(VisitedSet::with_bits(low, width + 1)),
+            _ => None,
+        }
+    }
+
+    fn with_bits(low: i128, len: u128) -> VisitedSet<T> {
+        VisitedSet {
+            low,
+            len,
+            bits: vec![0; len.div_ceil(64) as usize],
+            outside: HashSet::new(),
+        }
+    }
+
+    /// Creates a set suited to searching for the first repeat of the given
+    /// changes.
+    ///
+    /// The repeat happens within `spread / |drift|` passes, where the spread
+    /// is the difference between the highest and lowest frequencies of the
+    /// first pass, as that is the furthest that one frequency ever has to
+    /// catch up with another. So every frequency reached before the repeat is
+    /// within the spread of the first pass, extended by the spread again in
+    /// the direction of the drift. A bitset is used for that range unless it
+    /// has more than `BITSET_LIMIT` frequencies, or there is no repeat.
+    pub fn for_changes(changes: &[T]) -> VisitedSet<T> {
+        let sums = match checked_prefix_sums(changes.iter().cloned()) {
+            Ok(ref sums) if repeats_possible(sums) => sums.clone(),
+            _ => return VisitedSet::hashed(),
+        };
+
+        let drift = sums[sums.len() - 1].to_i128();
+        let min = sums.iter().min().unwrap().to_i128();
+        let max = sums.iter().max().unwrap().to_i128();
+
+        let spread = max.checked_sub(min);
+        let range = spread.and_then(|spread| match drift {
+            0 => Some((min, max)),
+            _ if drift > 0 => max.checked_add(spread).map(|high| (min, high)),
+            _ => min.checked_sub(spread).map(|low| (low, max)),
+        });
+
+        match range.and_then(|(low, high)| Some((low, high.checked_sub(low)? as u128))) {
+            Some((low, width)) if width < BITSET_LIMIT => VisitedSet::with_bits(low, width + 1),
+            _ => VisitedSet::hashed(),
+        }
+    }
+
+    /// Returns true if the set has a bitset for some range of frequencies.
+    pub fn is_bitset(&self) -> bool {
+        !self.bits.is_empty()
+    }
+
+    /// Adds the given frequency to the set, returning true if it was not
+    /// already there.
+    pub fn insert(&mut self, frequency: T) -> bool {
+        let position = frequency.to_i128().checked_sub(self.low)
+            .filter(|&position| position >= 0 && (position as u128) < self.len);
+
+        match position {
+            Some(position) => {
+                let (word, bit) = ((position / 64) as usize, position % 64);
+                let is_new = self.bits[word] & (1 << bit) == 0;
+                self.bits[word] |= 1 << bit;
+
+                is_new
+            }
+            None => self.outside.insert(frequency),
+        }
+    }
+}
+
+/// Returns the frequency after each of the given changes, in a single pass
+/// through them. The last of these is the drift of each pass. It panics if
+/// any of them overflow, see `checked_prefix_sums` for inputs that may do so.
+///
+/// ```
+/// use advent_of_code_2018::one;
+///
+/// assert_eq!(one::prefix_sums(&[2, 1, -4, 5]), vec![2, 3, -1, 4]);
+/// ```
+pub fn prefix_sums<I>(changes: I) -> Vec<<I::Item as Delta>::Frequency>
+    where I: IntoIterator, I::Item: Delta {
+    checked_prefix_sums(changes).unwrap_or_else(|overflow| panic!("{}", overflow))
+}
+
+/// Returns the frequency after each of the given changes in the same way as
+/// `prefix_sums`, or an error if any of them do not fit in their type.
+pub fn checked_prefix_sums<I>(changes: I) -> Result<Vec<<I::Item as Delta>::Frequency>, Overflow>
+    where I: IntoIterator, I::Item: Delta {
+    let mut sum = Frequency::zero();
+
+    changes.into_iter()
+        .enumerate()
+        .map(|(index, change)| {
+            sum = Frequency::checked_add(sum, change.value())
+                .ok_or(Overflow { pass: 0, index })?;
+
+            Ok(sum)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatError {
+    /// No frequency is ever reached twice, no matter how many passes are
+    /// made through the changes.
+    NoRepeat,
+    /// A frequency reached while searching for the repeat did not fit in its
+    /// type.
+    Overflow(Overflow),
+}
+
+impl fmt::Display for RepeatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RepeatError::NoRepeat => write!(f, "No frequency is ever reached twice"),
+            RepeatError::Overflow(ref overflow) => write!(f, "{}", overflow),
+        }
+    }
+}
+
+impl Error for RepeatError {}
+
+impl From<Overflow> for RepeatError {
+    fn from(overflow: Overflow) -> RepeatError {
+        RepeatError::Overflow(overflow)
+    }
+}
+
+/// Works out whether any frequency is ever reached twice when repeatedly
+/// applying the given changes, without having to search for the repeat.
+///
+/// After `k` more passes the frequency at each position has moved by `k`
+/// times the drift, the total of all the changes. So if the drift is zero,
+/// the second pass reaches every frequency of the first pass again. If it is
+/// non-zero, then the frequencies at two positions only ever meet if their
+/// first-pass frequencies are congruent modulo the drift.
+///
+/// It panics if the frequency overflows during the first pass.
+///
+/// ```
+/// use advent_of_code_2018::one;
+///
+/// assert_eq!(one::can_repeat(&[3, 3, 4, -2, -4]), true);
+/// assert_eq!(one::can_repeat(&[1, 1, 1]), false);
+/// ```
+pub fn can_repeat<T: Frequency>(changes: &[T]) -> bool {
+    repeats_possible(&prefix_sums(changes.iter().cloned()))
+}
+
+/// Works out whether there is a repeat in the same way as `can_repeat`,
+/// given the frequencies after each change in the first pass.
+fn repeats_possible<T: Frequency>(sums: &[T]) -> bool {
+    let drift = match sums.last() {
+        Some(&drift) => drift,
+        None => return false,
+    };
+
+    if drift == T::zero() {
+        return true;
+    }
+
+    let mut residues = HashSet::new();
+
+    !sums.iter().all(|&sum| residues.insert(sum.modulo(drift)))
+}
+
+/// Repeatedly re-sums up the given changes and returns the first repeated
+/// frequency, or an error if no frequency is ever repeated.
+///
+/// Unlike `first_repeat`, this always halts, as it checks whether there is a
+/// repeat before searching for it. It also reports an error rather than
+/// overflowing if the frequency does not fit in its type.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, RepeatError};
+///
+/// assert_eq!(one::find_first_repeat(&[-1, 1, 2]), Ok(2));
+/// assert_eq!(one::find_first_repeat(&[1, 1, 1]), Err(RepeatError::NoRepeat));
+/// ```
+pub fn find_first_repeat<I>(changes: I) -> Result<<I::Item as Delta>::Frequency, RepeatError>
+    where I: IntoIterator, I::Item: Delta {
+    find_first_repeat_with(changes, Strategy::Simulation)
+}
+
+/// The ways of searching for the first repeated frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Makes pass after pass through the changes, remembering every
+    /// frequency reached, until one is reached again.
+    Simulation,
+    /// Works out the repeat directly from the frequencies of the first pass,
+    /// in `O(n log n)` time no matter how many passes it takes.
+    ///
+    /// As it never goes past the first pass, only the frequencies of the
+    /// first pass need to fit in their type, so it can find repeats that the
+    /// simulation would overflow before reaching.
+    Analytic,
+}
+
+impl FromStr for Strategy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Strategy, ()> {
+        match s {
+            "simulation" => Ok(Strategy::Simulation),
+            "analytic" => Ok(Strategy::Analytic),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Returns the first repeated frequency, searching for it with the given
+/// strategy.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, Strategy};
+///
+/// let changes = [7, 7, -2, -7, -4];
+///
+/// assert_eq!(one::find_first_repeat_with(&changes, Strategy::Simulation), Ok(14));
+/// assert_eq!(one::find_first_repeat_with(&changes, Strategy::Analytic), Ok(14));
+/// ```
+pub fn find_first_repeat_with<I>(changes: I, strategy: Strategy) -> Result<<I::Item as Delta>::Frequency, RepeatError>
+    where I: IntoIterator, I::Item: Delta {
+    find_repeat_report(changes, strategy).map(|report| report.frequency)
+}
+
+/// Returns the first repeated frequency, given the frequencies after each
+/// change in the first pass (see `prefix_sums`).
+///
+/// Pass `k` reaches the frequency `sums[i] + k * drift` at position `i`.
+/// If the first pass has no repeats of its own, then a later repeat happens
+/// when position `i` catches up with the first-pass frequency of some other
+/// position `j`. That needs `sums[j] - sums[i]` to be a positive multiple of
+/// the drift, so it can only happen between positions in the same residue
+/// class modulo the drift. Within each class, sorted by frequency, each
+/// position first catches up with its neighbour in the direction of the
+/// drift, and the earliest of those catch-ups is the first repeat.
+pub fn analytic_first_repeat<T: Frequency>(sums: &[T]) -> Result<T, RepeatError> {
+    analytic_report(sums).map(|report| report.frequency)
+}
+
+/// Returns the details of the first repeat, given the frequencies after each
+/// change in the first pass, in the same way as `analytic_first_repeat`.
+///
+/// The repeated frequency is always first reached in the first pass, since
+/// every later frequency is a first-pass one shifted by the drift.
+pub fn analytic_report<T: Frequency>(sums: &[T]) -> Result<RepeatReport<T>, RepeatError> {
+    locate_repeat(sums)
+        .map(|(pass, index, first_index)| {
+            RepeatReport::new(sums.len(), sums[first_index], (pass, index), (0, first_index))
+        })
+        .ok_or(RepeatError::NoRepeat)
+}
+
+/// Finds the pass and position at which the first repeat happens, along with
+/// the first-pass position where that frequency was first reached.
+fn locate_repeat<T: Frequency>(sums: &[T]) -> Option<(u128, usize, usize)> {
+    let drift = *sums.last()?;
+
+    // Repeats within the first pass come before any later ones
+    let mut first_seen = HashMap::new();
+    for (i, &sum) in sums.iter().enumerate() {
+        if let Some(&j) = first_seen.get(&sum) {
+            return Some((0, i, j));
+        }
+
+        first_seen.insert(sum, i);
+    }
+
+    if drift == T::zero() {
+        return Some((1, 0, 0));
+    }
+
+    let mut classes: Vec<(T, T, usize)> = sums.iter().enumerate()
+        .map(|(i, &sum)| (sum.modulo(drift), sum, i))
+        .collect();
+    classes.sort_unstable();
+
+    classes.windows(2)
+        .filter(|pair| pair[0].0 == pair[1].0)
+        .map(|pair| {
+            // With a positive drift the lower frequency catches up with the
+            // higher one, and with a negative drift the other way around
+            let (chaser, target) = if drift > T::zero() {
+                (pair[0], pair[1])
+            } else {
+                (pair[1], pair[0])
+            };
+
+            // The distance may not fit in the frequency type, but it always
+            // fits in a u128 as the pair is in ascending order
+            let distance = pair[1].1.to_i128().wrapping_sub(pair[0].1.to_i128()) as u128;
+            let passes = distance / drift.to_i128().unsigned_abs();
+
+            (passes, chaser.2, target.2)
+        })
+        .min_by_key(|&(passes, index, _)| (passes, index))
+}
+
+/// Where the first repeated frequency is reached again, and where it was
+/// first reached. Passes and indices count from zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RepeatReport<T> {
+    pub frequency: T,
+    pub pass: u128,
+    /// The index of the change that reached the frequency again.
+    pub index: usize,
+    pub first_pass: u128,
+    /// The index of the change that first reached the frequency.
+    pub first_index: usize,
+    /// How many changes were applied up to and including the repeat, or
+    /// `u128::MAX` if there were more than that.
+    pub changes_applied: u128,
+}
+
+impl<T> RepeatReport<T> {
+    /// Creates a report from the frequency and the (pass, index) positions
+    /// where it was reached, given how many changes there are in each pass.
+    fn new(len: usize, frequency: T, (pass, index): (u128, usize), (first_pass, first_index): (u128, usize)) -> RepeatReport<T> {
+        RepeatReport {
+            frequency,
+            pass,
+            index,
+            first_pass,
+            first_index,
+            changes_applied: pass.saturating_mul(len as u128).saturating_add(index as u128 + 1),
+        }
+    }
+}
+
+impl<T: Frequency> RepeatReport<T> {
+    /// Converts the frequency to the widest frequency type.
+    pub fn widen(self) -> RepeatReport<i128> {
+        RepeatReport {
+            frequency: self.frequency.to_i128(),
+            pass: self.pass,
+            index: self.index,
+            first_pass: self.first_pass,
+            first_index: self.first_index,
+            changes_applied: self.changes_applied,
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for RepeatReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Frequency:       {}", self.frequency)?;
+        writeln!(f, "Repeated at:     pass {}, change {}", self.pass, self.index)?;
+        writeln!(f, "First reached:   pass {}, change {}", self.first_pass, self.first_index)?;
+        writeln!(f, "Changes applied: {}", self.changes_applied)
+    }
+}
+
+/// Returns the details of the first repeated frequency, searching for it
+/// with the given strategy, or an error if no frequency is ever repeated or
+/// if the search overflows.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, Strategy};
+///
+/// let report = one::find_repeat_report(&[3, 3, 4, -2, -4], Strategy::Simulation).unwrap();
+///
+/// assert_eq!(report.frequency, 10);
+/// assert_eq!((report.pass, report.index), (1, 1));
+/// assert_eq!((report.first_pass, report.first_index), (0, 2));
+/// assert_eq!(report.changes_applied, 7);
+/// ```
+pub fn find_repeat_report<I>(changes: I, strategy: Strategy) -> Result<RepeatReport<<I::Item as Delta>::Frequency>, RepeatError>
+    where I: IntoIterator, I::Item: Delta {
+    let changes: Vec<_> = changes.into_iter().map(Delta::value).collect();
+    let sums = checked_prefix_sums(changes.iter().cloned())?;
+
+    match strategy {
+        Strategy::Simulation if repeats_possible(&sums) => Ok(simulate_report(&changes)?),
+        Strategy::Simulation => Err(RepeatError::NoRepeat),
+        Strategy::Analytic => analytic_report(&sums),
+    }
+}
+
+/// Makes passes through the changes until a frequency is reached again,
+/// remembering the frequencies reached in the set that `VisitedSet::for_changes`
+/// picks for them.
+///
+/// The set does not say where a frequency was first reached, so once the
+/// repeat is found the walk is made again up to its first visit. That is
+/// still much faster than hashing every frequency along with its position.
+///
+/// If no repeats ever occur, then this function will not halt.
+fn simulate_report<T: Frequency>(changes: &[T]) -> Result<RepeatReport<T>, Overflow> {
+    let mut visited = VisitedSet::for_changes(changes);
+    let mut walk = FrequencyWalk::new(changes.iter().cloned());
+
+    let repeat = walk.by_ref().find(|&(_, _, _, frequency)| !visited.insert(frequency));
+    let (pass, index, _, frequency) = match repeat {
+        Some(step) => step,
+        None => return Err(walk.overflow().expect("The walk only ends early if there are no changes")),
+    };
+
+    let (first_pass, first_index, _, _) = FrequencyWalk::new(changes.iter().cloned())
+        .find(|&(_, _, _, reached)| reached == frequency)
+        .expect("The repeated frequency was reached before");
+
+    Ok(RepeatReport::new(changes.len(), frequency, (pass, index), (first_pass, first_index)))
+}
+
+/// Returns the first frequency to be reached the given number of times, or
+/// an error if no frequency is ever reached that many times. Zero times is
+/// taken as once.
+///
+/// With a non-zero drift each position reaches any frequency at most once,
+/// and only frequencies congruent to its own first-pass frequency modulo the
+/// drift. So a frequency can only be reached `k` times if at least `k`
+/// positions are congruent, and then all of them reach every frequency far
+/// enough along in the direction of the drift. With a zero drift, every
+/// frequency of the first pass is reached again on every pass.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, RepeatError};
+///
+/// assert_eq!(one::first_reached_times(&[1, 1, 1, -2], 3), Ok(2));
+/// assert_eq!(one::first_reached_times(&[3, 3, 4, -2, -4], 3), Err(RepeatError::NoRepeat));
+/// ```
+pub fn first_reached_times<I>(changes: I, times: usize) -> Result<<I::Item as Delta>::Frequency, RepeatError>
+    where I: IntoIterator, I::Item: Delta {
+    let changes: Vec<_> = changes.into_iter().map(Delta::value).collect();
+    let sums = checked_prefix_sums(changes.iter().cloned())?;
+
+    if !can_reach_times(&sums, times) {
+        return Err(RepeatError::NoRepeat);
+    }
+
+    let mut counts = HashMap::new();
+    let mut walk = FrequencyWalk::new(changes);
+
+    for (_, _, _, frequency) in walk.by_ref() {
+        let count = counts.entry(frequency).or_insert(0);
+        *count += 1;
+
+        if *count >= times {
+            return Ok(frequency);
+        }
+    }
+
+    Err(walk.overflow().expect("The walk only ends early if there are no changes").into())
+}
+
+/// Works out whether any frequency is reached the given number of times,
+/// given the frequencies after each change in the first pass.
+fn can_reach_times<T: Frequency>(sums: &[T], times: usize) -> bool {
+    let drift = match sums.last() {
+        Some(&drift) => drift,
+        None => return false,
+    };
+
+    if drift == T::zero() || times <= 1 {
+        return true;
+    }
+
+    let mut class_sizes = HashMap::new();
+
+    sums.iter().any(|&sum| {
+        let size = class_sizes.entry(sum.modulo(drift)).or_insert(0);
+        *size += 1;
+
+        *size >= times
+    })
+}
+
+/// Returns the first frequencies to be reached twice, up to the given number
+/// of them, in the order that they are reached for the second time.
+///
+/// Fewer are returned only if there are no more repeated frequencies. With
+/// a zero drift those are the distinct frequencies of the first pass, and
+/// with a non-zero drift there are either none or endlessly many, as any two
+/// congruent positions meet again on every frequency past the higher of them.
+///
+/// ```
+/// use advent_of_code_2018::one;
+///
+/// assert_eq!(one::first_repeats(&[3, 3, 4, -2, -4], 3), Ok(vec![10, 8, 14]));
+/// assert_eq!(one::first_repeats(&[1, -1], 5), Ok(vec![1, 0]));
+/// assert_eq!(one::first_repeats(&[1, 1, 1], 5), Ok(vec![]));
+/// ```
+pub fn first_repeats<I>(changes: I, count: usize) -> Result<Vec<<I::Item as Delta>::Frequency>, Overflow>
+    where I: IntoIterator, I::Item: Delta {
+    let changes: Vec<_> = changes.into_iter().map(Delta::value).collect();
+    let sums = checked_prefix_sums(changes.iter().cloned())?;
+
+    let available = match sums.last() {
+        Some(&drift) if drift == Frequency::zero() => {
+            sums.iter().collect::<HashSet<_>>().len()
+        }
+        Some(_) if repeats_possible(&sums) => count,
+        _ => 0,
+    };
+    let count = count.min(available);
+
+    let mut repeats = Vec::new();
+    if count == 0 {
+        return Ok(repeats);
+    }
+
+    let mut visits = HashMap::new();
+    let mut walk = FrequencyWalk::new(changes);
+
+    for (_, _, _, frequency) in walk.by_ref() {
+        let visit = visits.entry(frequency).or_insert(0);
+        *visit += 1;
+
+        if *visit == 2 {
+            repeats.push(frequency);
+
+            if repeats.len() == count {
+                return Ok(repeats);
+            }
+        }
+    }
+
+    Err(walk.overflow().expect("The walk only ends early if there are no changes"))
+}
+
+/// Counts how many times each frequency is reached within the given number
+/// of passes through the changes.
+///
+/// ```
+/// use advent_of_code_2018::one;
+///
+/// let counts = one::visit_counts(&[1, -1, 3], 2).unwrap();
+///
+/// assert_eq!(counts.into_iter().collect::<Vec<_>>(), vec![(0, 1), (1, 1), (3, 2), (4, 1), (6, 1)]);
+/// ```
+pub fn visit_counts<I>(changes: I, passes: u128) -> Result<BTreeMap<<I::Item as Delta>::Frequency, usize>, Overflow>
+    where I: IntoIterator, I::Item: Delta {
+    let mut counts = BTreeMap::new();
+    let mut walk = FrequencyWalk::new(changes);
+
+    for (pass, _, _, frequency) in walk.by_ref() {
+        if pass >= passes {
+            break;
+        }
+
+        *counts.entry(frequency).or_insert(0) += 1;
+    }
+
+    match walk.overflow() {
+        Some(overflow) if overflow.pass < passes => Err(overflow),
+        _ => Ok(counts),
+    }
+}
+
+/// Returns the pass and the index of the change at which the frequency first
+/// equals the target, both counting from zero, or `None` if it never does.
+///
+/// Pass `k` reaches `sums[i] + k * drift` at position `i`, so each position
+/// reaches the target on at most one pass, if the distance to it is a
+/// multiple of the drift in the direction of the drift. This is worked out
+/// for every position from the first pass, so it never has to loop. The
+/// starting frequency of zero only counts once a change reaches it.
+///
+/// ```
+/// use advent_of_code_2018::one;
+///
+/// assert_eq!(one::first_time_reaching(&[3, 3, 4, -2, -4], 22), Ok(Some((3, 2))));
+/// assert_eq!(one::first_time_reaching(&[3, 3, 4, -2, -4], 5), Ok(None));
+/// ```
+pub fn first_time_reaching<I>(changes: I, target: <I::Item as Delta>::Frequency) -> Result<Option<(u128, usize)>, Overflow>
+    where I: IntoIterator, I::Item: Delta {
+    let sums = checked_prefix_sums(changes)?;
+
+    let drift = match sums.last() {
+        Some(&drift) => drift,
+        None => return Ok(None),
+    };
+
+    let reached = sums.iter().enumerate().filter_map(|(index, &sum)| {
+        if sum == target {
+            return Some((0, index));
+        }
+
+        let zero = Frequency::zero();
+        let towards_target = if target > sum { drift > zero } else { drift < zero };
+        if !towards_target {
+            return None;
+        }
+
+        // The distance may not fit in the frequency type, but it always
+        // fits in a u128 when taken from the lower value
+        let (low, high) = if target > sum { (sum, target) } else { (target, sum) };
+        let distance = high.to_i128().wrapping_sub(low.to_i128()) as u128;
+        let step = drift.to_i128().unsigned_abs();
+
+        match distance % step {
+            0 => Some((distance / step, index)),
+            _ => None,
+        }
+    });
+
+    Ok(reached.min())
+}
+
+/// How the frequency moves over a pass through the changes, and how far it
+/// drifts between passes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DriftReport<T> {
+    /// How far every frequency moves from one pass to the next.
+    pub drift: T,
+    /// The lowest frequency reached in the first pass. Each later pass is
+    /// shifted by the drift.
+    pub min: T,
+    /// The highest frequency reached in the first pass.
+    pub max: T,
+    /// The difference between the highest and lowest frequencies.
+    pub spread: u128,
+    /// How many passes the simulation makes before finding the first repeat,
+    /// or `None` if no frequency is ever repeated.
+    pub passes_needed: Option<u128>,
+    /// The frequency after each change in the first pass.
+    #[serde(skip)]
+    pub sums: Vec<T>,
+}
+
+/// Works out how the frequency drifts when applying the given changes. With
+/// no changes at all, the frequency stays at zero and never repeats.
+///
+/// The passes needed are worked out in the same way as
+/// `analytic_first_repeat`, so they are exact, without having to simulate.
+///
+/// ```
+/// use advent_of_code_2018::one;
+///
+/// let report = one::drift_report(&[3, 3, 4, -2, -4]).unwrap();
+///
+/// assert_eq!(report.drift, 4);
+/// assert_eq!((report.min, report.max, report.spread), (3, 10, 7));
+/// assert_eq!(report.passes_needed, Some(2));
+/// ```
+pub fn drift_report<I>(changes: I) -> Result<DriftReport<<I::Item as Delta>::Frequency>, Overflow>
+    where I: IntoIterator, I::Item: Delta {
+    let sums = checked_prefix_sums(changes)?;
+
+    let drift = sums.last().cloned().unwrap_or_else(Frequency::zero);
+    let min = sums.iter().cloned().min().unwrap_or(drift);
+    let max = sums.iter().cloned().max().unwrap_or(drift);
+
+    Ok(DriftReport {
+        drift,
+        min,
+        max,
+        spread: max.to_i128().wrapping_sub(min.to_i128()) as u128,
+        passes_needed: analytic_report(&sums).ok().map(|report| report.pass + 1),
+        sums,
+    })
+}
+
+impl<T: Frequency> DriftReport<T> {
+    /// Converts the frequencies to the widest frequency type.
+    pub fn widen(self) -> DriftReport<i128> {
+        DriftReport {
+            drift: self.drift.to_i128(),
+            min: self.min.to_i128(),
+            max: self.max.to_i128(),
+            spread: self.spread,
+            passes_needed: self.passes_needed,
+            sums: self.sums.into_iter().map(Frequency::to_i128).collect(),
+        }
+    }
+
+    /// Draws a line chart of the frequency over the first pass, fitting
+    /// within the given number of columns and rows.
+    ///
+    /// When there are more changes than columns, each column covers several
+    /// changes and spans the range of frequencies that they reach. There is
+    /// no chart if there are no changes.
+    ///
+    /// ```
+    /// use advent_of_code_2018::one;
+    ///
+    /// let report = one::drift_report(&[1, 1, -1, -2]).unwrap();
+    ///
+    /// let expected = [
+    ///     " 2| *",
+    ///     "  |* *",
+    ///     "-1|   *",
+    ///     "  +----",
+    ///     "   0  4",
+    /// ];
+    ///
+    /// assert_eq!(report.chart(80, 3).lines().collect::<Vec<_>>(), expected);
+    /// ```
+    pub fn chart(&self, columns: usize, rows: usize) -> String {
+        if self.sums.is_empty() {
+            return String::new();
+        }
+
+        let (min, max) = (self.min.to_i128(), self.max.to_i128());
+        let (max_label, min_label) = (max.to_string(), min.to_string());
+        let label_width = max_label.len().max(min_label.len());
+
+        let rows = rows.max(2);
+        let width = columns.saturating_sub(label_width + 1).max(1).min(self.sums.len());
+
+        // Row 0 is the top of the chart, where the highest frequency is
+        let row_of = |frequency: T| {
+            if max == min {
+                return rows / 2;
+            }
+
+            let height = (max as f64 - frequency.to_i128() as f64) / (max as f64 - min as f64);
+
+            (height * (rows - 1) as f64).round() as usize
+        };
+
+        let mut grid = vec![vec![' '; width]; rows];
+        for column in 0..width {
+            let start = column * self.sums.len() / width;
+            let end = (column + 1) * self.sums.len() / width;
+            let bucket = &self.sums[start..end];
+
+            let top = row_of(*bucket.iter().max().unwrap());
+            let bottom = row_of(*bucket.iter().min().unwrap());
+            for line in grid[top..=bottom].iter_mut() {
+                line[column] = '*';
+            }
+        }
+
+        let mut chart = String::new();
+        for (row, line) in grid.iter().enumerate() {
+            let label = match row {
+                0 => &max_label,
+                _ if row == rows - 1 => &min_label,
+                _ => "",
+            };
+            let line: String = line.iter().collect();
+
+            chart.push_str(&format!("{:>w$}|{}\n", label, line.trim_end(), w = label_width));
+        }
+
+        chart.push_str(&format!("{:w$}+{}\n", "", "-".repeat(width), w = label_width));
+        chart.push_str(&format!(
+            "{:w$} 0{:>c$}\n", "", self.sums.len(), w = label_width, c = width.saturating_sub(1)
+        ));
+
+        chart
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for DriftReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Drift per pass:  {}", self.drift)?;
+        writeln!(f, "First pass:      {} to {}", self.min, self.max)?;
+        writeln!(f, "Spread:          {}", self.spread)?;
+
+        match self.passes_needed {
+            Some(passes) => writeln!(f, "Passes needed:   {}", passes),
+            None => writeln!(f, "Passes needed:   never repeats"),
+        }
+    }
+}
+
+/// A way of changing a single line of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Edit {
+    /// Flips the sign of the change.
+    Negate,
+    /// Leaves the change out.
+    Remove,
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Edit::Negate => write!(f, "negate"),
+            Edit::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+/// What the answers would be if a single change were edited.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WhatIf<T> {
+    /// The index of the edited change, counting from zero.
+    pub index: usize,
+    pub edit: Edit,
+    pub sum: T,
+    /// The first repeated frequency, or `None` if there would be none.
+    pub first_repeat: Option<T>,
+    /// How far the sum moves from the unedited one.
+    pub sum_shift: u128,
+    /// How far the first repeat moves from the unedited one, or `None` if
+    /// the edit makes the frequencies start or stop repeating.
+    pub repeat_shift: Option<u128>,
+}
+
+/// The answers for the unedited changes, along with what they would be for
+/// each edit, from the edit with the most impact to the least.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WhatIfReport<T> {
+    pub sum: T,
+    pub first_repeat: Option<T>,
+    pub edits: Vec<WhatIf<T>>,
+}
+
+/// Works out the sum and the first repeat if each change in turn were
+/// negated or removed, and ranks the edits by how far they move the first
+/// repeat, and then by how far they move the sum. Edits that make the
+/// frequencies start or stop repeating rank highest.
+///
+/// Editing a change only shifts the frequencies from that change onwards,
+/// all by the same amount, so the frequencies of each edited first pass are
+/// made by shifting the unedited ones. The first repeat is then worked out
+/// from them in the same way as `analytic_first_repeat`.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, Edit};
+///
+/// let report = one::what_if(&[1, 1, -1]).unwrap();
+///
+/// assert_eq!((report.sum, report.first_repeat), (1, Some(1)));
+///
+/// // Negating the last change gives 1, 2, 3, which never repeat
+/// let best = &report.edits[0];
+/// assert_eq!((best.index, best.edit), (2, Edit::Negate));
+/// assert_eq!((best.sum, best.first_repeat), (3, None));
+/// ```
+pub fn what_if<T: Frequency>(changes: &[T]) -> Result<WhatIfReport<T>, Overflow> {
+    let sums = checked_prefix_sums(changes.iter().cloned())?;
+    let sum = sums.last().cloned().unwrap_or_else(T::zero);
+    let first_repeat = analytic_first_repeat(&sums).ok();
+
+    let mut edits = Vec::new();
+    for (index, &change) in changes.iter().enumerate() {
+        for &edit in [Edit::Negate, Edit::Remove].iter() {
+            let edited = edited_sums(&sums, change, index, edit)?;
+            let edited_sum = edited.last().cloned().unwrap_or_else(T::zero);
+            let edited_repeat = analytic_first_repeat(&edited).ok();
+
+            let repeat_shift = match (first_repeat, edited_repeat) {
+                (Some(a), Some(b)) => Some(distance(a, b)),
+                (None, None) => Some(0),
+                _ => None,
+            };
+
+            edits.push(WhatIf {
+                index,
+                edit,
+                sum: edited_sum,
+                first_repeat: edited_repeat,
+                sum_shift: distance(sum, edited_sum),
+                repeat_shift,
+            });
+        }
+    }
+
+    edits.sort_by_key(|w| Reverse((w.repeat_shift.is_none(), w.repeat_shift, w.sum_shift)));
+
+    Ok(WhatIfReport { sum, first_repeat, edits })
+}
+
+/// Returns the frequencies of the first pass after making the given edit to
+/// the change at the given index, by shifting the unedited frequencies.
+fn edited_sums<T: Frequency>(sums: &[T], change: T, index: usize, edit: Edit) -> Result<Vec<T>, Overflow> {
+    let mut edited = sums[..index].to_vec();
+
+    let (shifted, times) = match edit {
+        Edit::Negate => (&sums[index..], 2),
+        Edit::Remove => (&sums[index + 1..], 1),
+    };
+
+    for (offset, &sum) in shifted.iter().enumerate() {
+        let mut sum = Some(sum);
+        for _ in 0..times {
+            sum = sum.and_then(|sum| Frequency::checked_sub(sum, change));
+        }
+
+        edited.push(sum.ok_or(Overflow { pass: 0, index: index + offset })?);
+    }
+
+    Ok(edited)
+}
+
+/// Returns the distance between two frequencies, which always fits in a
+/// u128.
+fn distance<T: Frequency>(a: T, b: T) -> u128 {
+    let (low, high) = if a < b { (a, b) } else { (b, a) };
+
+    high.to_i128().wrapping_sub(low.to_i128()) as u128
+}
+
+impl<T: Frequency> WhatIfReport<T> {
+    /// Converts the frequencies to the widest frequency type.
+    pub fn widen(self) -> WhatIfReport<i128> {
+        WhatIfReport {
+            sum: self.sum.to_i128(),
+            first_repeat: self.first_repeat.map(Frequency::to_i128),
+            edits: self.edits.into_iter()
+                .map(|w| WhatIf {
+                    index: w.index,
+                    edit: w.edit,
+                    sum: w.sum.to_i128(),
+                    first_repeat: w.first_repeat.map(Frequency::to_i128),
+                    sum_shift: w.sum_shift,
+                    repeat_shift: w.repeat_shift,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for WhatIfReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn repeat<T: fmt::Display>(repeat: &Option<T>) -> String {
+            repeat.as_ref().map_or("never".to_string(), |r| r.to_string())
+        }
+
+        writeln!(f, "Sum:          {}", self.sum)?;
+        writeln!(f, "First repeat: {}", repeat(&self.first_repeat))?;
+        writeln!(f)?;
+        writeln!(
+            f, "{:>4}  {:>6}  {:<6}  {:>12}  {:>12}  {:>12}",
+            "Rank", "Change", "Edit", "Sum", "First repeat", "Shift"
+        )?;
+        for (rank, w) in self.edits.iter().enumerate() {
+            let shift = w.repeat_shift.map_or("-".to_string(), |s| s.to_string());
+
+            writeln!(
+                f, "{:>4}  {:>6}  {:<6}  {:>12}  {:>12}  {:>12}",
+                rank + 1, w.index + 1, w.edit, w.sum, repeat(&w.first_repeat), shift
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A change in the operation language used by puzzles derived from day 1,
+/// which can do more to the running frequency than add to it.
+///
+/// Changes are written as `+N` or `-N` to add, `*N` to multiply, `=N` to
+/// set the frequency to `N`, and `%N` to take the remainder of dividing by
+/// `N`, which is never negative.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, Change};
+///
+/// let changes = one::parse_changes::<Change<i32>>("+5, *2\n%7, =-1").unwrap();
+///
+/// assert_eq!(changes, vec![Change::Add(5), Change::Mul(2), Change::Mod(7), Change::Set(-1)]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Change<T> {
+    Add(T),
+    Mul(T),
+    Set(T),
+    Mod(T),
+}
+
+impl<T: Frequency> Change<T> {
+    /// Applies the change to the given frequency, returning `None` if the
+    /// result does not fit.
+    pub fn apply(self, frequency: T) -> Option<T> {
+        match self {
+            Change::Add(n) => frequency.checked_add(n),
+            Change::Mul(n) => frequency.checked_mul(n),
+            Change::Set(n) => Some(n),
+            Change::Mod(n) => frequency.checked_modulo(n),
+        }
+    }
+}
+
+impl<T: Frequency + FromStr> FromStr for Change<T> {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Change<T>, ()> {
+        let operand = |n: &str| n.parse::<T>().map_err(|_| ());
+
+        match s.chars().next() {
+            Some('*') => operand(&s[1..]).map(Change::Mul),
+            Some('=') => operand(&s[1..]).map(Change::Set),
+            Some('%') => match operand(&s[1..])? {
+                // The remainder of dividing by zero is not defined
+                n if n == T::zero() => Err(()),
+                n => Ok(Change::Mod(n)),
+            },
+            _ => operand(s).map(Change::Add),
+        }
+    }
+}
+
+impl<T: Frequency> fmt::Display for Change<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Change::Add(n) if n < T::zero() => write!(f, "{}", n),
+            Change::Add(n) => write!(f, "+{}", n),
+            Change::Mul(n) => write!(f, "*{}", n),
+            Change::Set(n) => write!(f, "={}", n),
+            Change::Mod(n) => write!(f, "%{}", n),
+        }
+    }
+}
+
+/// Returns the amounts added by the given changes if they only add to the
+/// frequency, in which case the faster additive functions apply to them.
+fn additive<T: Frequency>(changes: &[Change<T>]) -> Option<Vec<T>> {
+    changes.iter()
+        .map(|&change| match change {
+            Change::Add(n) => Some(n),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parses the changes in the same way as `parse_changes`, returning an error
+/// if any of them do not add to the frequency.
+fn parse_additions<T: Frequency + FromStr>(input: &str) -> Result<Vec<T>, PartError> {
+    let changes: Vec<Change<T>> = parse_changes(input)?;
+
+    changes.iter()
+        .enumerate()
+        .map(|(index, &change)| match change {
+            Change::Add(n) => Ok(n),
+            _ => Err(PartError::Unsupported { index, change: change.to_string() }),
+        })
+        .collect()
+}
+
+/// Applies each of the changes in turn to a frequency starting from zero,
+/// and returns the resulting frequency, or an error giving the index of the
+/// change at which the frequency no longer fits in its type.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, Change, Overflow};
+///
+/// let changes = [Change::Add(5), Change::Mul(3), Change::Mod(4), Change::Add(-3)];
+///
+/// assert_eq!(one::evaluate(&changes), Ok(0));
+/// assert_eq!(one::evaluate(&[Change::Set(i32::MAX), Change::Mul(2)]), Err(Overflow { pass: 0, index: 1 }));
+/// ```
+pub fn evaluate<T: Frequency>(changes: &[Change<T>]) -> Result<T, Overflow> {
+    changes.iter().enumerate().try_fold(T::zero(), |frequency, (index, change)| {
+        change.apply(frequency).ok_or(Overflow { pass: 0, index })
+    })
+}
+
+/// Returns the details of the first frequency to be reached twice when
+/// applying the given changes over and over again, starting from zero.
+///
+/// Changes that only add are searched with the given strategy, as in
+/// `find_repeat_report`. Otherwise the frequencies no longer drift by the
+/// same amount on each pass, so the changes are simulated instead. Each
+/// frequency only depends on the index of the change and the frequency
+/// before it, and there are only so many of those pairs, so the walk through
+/// them must eventually run into one that it has already seen. The frequency
+/// of that pair has then been reached twice, so unless the frequency
+/// overflows first, a repeat is always found.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, Change, Strategy};
+///
+/// let changes = [Change::Add(5), Change::Mul(2), Change::Mod(7)];
+/// let report = one::program_repeat_report(&changes, Strategy::Simulation).unwrap();
+///
+/// // 5, 10, 3, then 8, 16, 2, then 7, 14, 0, then 5 again
+/// assert_eq!(report.frequency, 5);
+/// assert_eq!((report.pass, report.index), (3, 0));
+/// assert_eq!((report.first_pass, report.first_index), (0, 0));
+/// ```
+pub fn program_repeat_report<T: Frequency>(changes: &[Change<T>], strategy: Strategy) -> Result<RepeatReport<T>, RepeatError> {
+    if let Some(deltas) = additive(changes) {
+        return find_repeat_report(deltas, strategy);
+    }
+
+    let mut first_seen = HashMap::new();
+    let mut frequency = T::zero();
+
+    for pass in 0.. {
+        for (index, change) in changes.iter().enumerate() {
+            frequency = change.apply(frequency).ok_or(Overflow { pass, index })?;
+
+            if let Some(&first) = first_seen.get(&frequency) {
+                return Ok(RepeatReport::new(changes.len(), frequency, (pass, index), first));
+            }
+
+            first_seen.insert(frequency, (pass, index));
+        }
+    }
+
+    unreachable!("Some change is not an addition, so the changes are not empty")
+}
+
+/// Sums up the provided changes and returns the resulting frequency. It
+/// panics if the frequency overflows.
+///
+/// This is kept for compatibility, `sum` takes any collection of changes.
+///
+/// ```
+/// use std::collections::LinkedList;
+/// use advent_of_code_2018::one;
+///
+/// let mut changes = LinkedList::new();
+///
+/// changes.push_back(2);
+/// changes.push_back(1);
+/// changes.push_back(-4);
+/// changes.push_back(5);
+///
+/// let res = one::sum_changes(&changes);
+///
+/// assert_eq!(res, 4);
+/// ```
+pub fn sum_changes(changes: &LinkedList<i32>) -> i32 {
+    sum(changes)
+}
+
+/// Repeatedly re-sums up the given changes and returns the first repeated
+/// frequency.
+///
+/// If no repeats ever occur, then this function will not halt.
+///
+/// This is kept for compatibility, `first_repeat` takes any collection of
+/// changes.
+///
+/// ```
+/// use std::collections::LinkedList;
+/// use advent_of_code_2018::one;
+///
+/// let mut changes = LinkedList::new();
+///
+/// changes.push_back(2);
+/// changes.push_back(1);
+/// changes.push_back(-1);
+/// changes.push_back(5);
+///
+/// let res = one::get_first_repeat_frequency(&changes);
+///
+/// assert_eq!(res, 2);
+/// ```
+pub fn get_first_repeat_frequency(changes: &LinkedList<i32>) -> i32 {
+    first_repeat(changes)
+}
+
+/// How the changes of several devices are interleaved into a single stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Merge {
+    /// One change from each device in turn, skipping devices that have run
+    /// out of changes.
+    RoundRobin,
+    /// In order of the timestamps in front of the changes. Changes with
+    /// equal timestamps keep the order of their devices, and their order
+    /// within each device.
+    Timestamp,
+}
+
+impl FromStr for Merge {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Merge, ()> {
+        match s {
+            "round-robin" => Ok(Merge::RoundRobin),
+            "timestamp" => Ok(Merge::Timestamp),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A change in the input of one of several devices that could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceError {
+    /// The device whose input it is, counting from zero.
+    pub device: usize,
+    pub error: ParseError,
+}
+
+impl fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} in the input of device {}", self.error, self.device + 1)
+    }
+}
+
+impl Error for DeviceError {}
+
+/// Something that went wrong while merging the changes of several devices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    Device(DeviceError),
+    Overflow(Overflow),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MergeError::Device(ref err) => write!(f, "{}", err),
+            MergeError::Overflow(ref overflow) => write!(f, "{}", overflow),
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+impl From<DeviceError> for MergeError {
+    fn from(err: DeviceError) -> MergeError {
+        MergeError::Device(err)
+    }
+}
+
+impl From<Overflow> for MergeError {
+    fn from(overflow: Overflow) -> MergeError {
+        MergeError::Overflow(overflow)
+    }
+}
+
+/// Interleaves the changes of several devices into a single list, in the
+/// order given by the merge policy. The changes may be anything that
+/// `parse_changes` can parse, such as integers or `Change`s.
+///
+/// For `Merge::Timestamp`, each line of changes starts with a timestamp in
+/// square brackets, such as `[1518-11-01 00:05] +3, -1`. The timestamps are
+/// compared as text, so they should all have the same form and width.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, Merge};
+///
+/// let merged = one::merge_devices::<i32, _>(&["+1\n+2\n+3", "-1\n-2"], Merge::RoundRobin).unwrap();
+/// assert_eq!(merged, vec![1, -1, 2, -2, 3]);
+///
+/// let devices = ["[03] +1\n[05] +2", "[01] -1\n[03] -2"];
+/// let merged = one::merge_devices::<i32, _>(&devices, Merge::Timestamp).unwrap();
+/// assert_eq!(merged, vec![-1, 1, -2, 2]);
+/// ```
+pub fn merge_devices<C, S>(inputs: &[S], merge: Merge) -> Result<Vec<C>, DeviceError>
+    where C: FromStr, S: AsRef<str> {
+    match merge {
+        Merge::RoundRobin => {
+            let mut devices = Vec::new();
+            for (device, input) in inputs.iter().enumerate() {
+                let changes: Vec<C> = parse_changes(input.as_ref())
+                    .map_err(|error| DeviceError { device, error })?;
+
+                devices.push(changes.into_iter());
+            }
+
+            let mut merged = Vec::new();
+            loop {
+                let before = merged.len();
+                merged.extend(devices.iter_mut().filter_map(Iterator::next));
+
+                if merged.len() == before {
+                    return Ok(merged);
+                }
+            }
+        }
+        Merge::Timestamp => {
+            let mut stamped = Vec::new();
+            for (device, input) in inputs.iter().enumerate() {
+                parse_stamped(input.as_ref(), &mut stamped)
+                    .map_err(|error| DeviceError { device, error })?;
+            }
+
+            // The sort is stable, so equal timestamps keep the input order
+            stamped.sort_by_key(|&(timestamp, _)| timestamp);
+
+            Ok(stamped.into_iter().map(|(_, change)| change).collect())
+        }
+    }
+}
+
+/// Parses lines of changes that each start with a timestamp in square
+/// brackets, adding the changes to the given list along with their
+/// timestamps.
+fn parse_stamped<'a, C: FromStr>(input: &'a str, stamped: &mut Vec<(&'a str, C)>) -> Result<(), ParseError> {
+    for (line_index, line) in input.lines().enumerate() {
+        let content = line.split('#').next().unwrap_or("");
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let start = line.len() - line.trim_start().len();
+        let end = match line[start..].strip_prefix('[').and_then(|rest| rest.find(']')) {
+            Some(end) => start + end + 2,
+            None => {
+                return Err(ParseError {
+                    line: line_index + 1,
+                    column: line[..start].chars().count() + 1,
+                    token: content.trim().to_string(),
+                });
+            }
+        };
+
+        let timestamp = &line[start + 1..end - 1];
+        parse_line(&line[end..], line_index, |change| {
+            stamped.push((timestamp, change));
+            Ok(())
+        }).map_err(|e: ParseError| ParseError {
+            column: e.column + line[..end].chars().count(),
+            ..e
+        })?;
+    }
+
+    Ok(())
+}
+
+/// The final frequency and the first repeat of the changes of several
+/// devices, once merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MergeReport {
+    /// How many changes there are in the merged stream.
+    pub changes: usize,
+    pub frequency: i128,
+    /// The first repeated frequency, or `None` if there is none.
+    pub first_repeat: Option<i128>,
+}
+
+impl fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Changes:      {}", self.changes)?;
+        writeln!(f, "Frequency:    {}", self.frequency)?;
+        match self.first_repeat {
+            Some(repeat) => writeln!(f, "First repeat: {}", repeat),
+            None => writeln!(f, "First repeat: never"),
+        }
+    }
+}
+
+/// Merges the changes of several devices with `merge_devices`, and works out
+/// the final frequency and the first repeat of the merged stream, which is
+/// applied over and over again as a whole. The frequencies are measured in
+/// the given integer type, and the changes may use any of the operations of
+/// `Change`, as in `program_repeat_report`.
+///
+/// This uses `evaluate` and `program_repeat_report` rather than
+/// `sum_changes` and `get_first_repeat_frequency`, since those only take
+/// plain `i32` additions, panic on overflow, and never halt if there is no
+/// repeat.
+///
+/// ```
+/// use advent_of_code_2018::one::{self, Merge, Width};
+///
+/// // Merges into +3, -2, +3, +1, -4
+/// let report = one::merge_report(&["+3\n+3\n-4", "-2\n+1"], Merge::RoundRobin, Width::I32).unwrap();
+///
+/// assert_eq!(report.frequency, 1);
+/// assert_eq!(report.first_repeat, Some(1));
+/// ```
+pub fn merge_report<S: AsRef<str>>(inputs: &[S], merge: Merge, width: Width) -> Result<MergeReport, MergeError> {
+    fn report<T: Frequency + FromStr, S: AsRef<str>>(inputs: &[S], merge: Merge) -> Result<MergeReport, MergeError> {
+        let changes: Vec<Change<T>> = merge_devices(inputs, merge)?;
+
+        let first_repeat = match program_repeat_report(&changes, Strategy::Analytic) {
+            Ok(report) => Some(report.frequency.to_i128()),
+            Err(RepeatError::NoRepeat) => None,
+            Err(RepeatError::Overflow(overflow)) => return Err(MergeError::Overflow(overflow)),
+        };
+
+        Ok(MergeReport {
+            changes: changes.len(),
+            frequency: evaluate(&changes)?.to_i128(),
+            first_repeat,
+        })
+    }
+
+    match width {
+        Width::I32 => report::<i32, S>(inputs, merge),
+        Width::I64 => report::<i64, S>(inputs, merge),
+        Width::I128 => report::<i128, S>(inputs, merge),
+    }
+}
+
+/// Builds lists of changes whose first repeat is known ahead of time, for
+/// testing the searches for it.
+///
+/// The lists are random, from a seeded xorshift generator, so the same seed
+/// always gives the same lists.
+///
+/// ```
+/// use std::collections::LinkedList;
+///
+/// use advent_of_code_2018::one::{self, Generator};
+///
+/// let mut generator = Generator::new(2018);
+///
+/// let changes: LinkedList<i32> = generator.with_repeat(50, 7, 12, -300).unwrap().into_iter().collect();
+/// assert_eq!(one::get_first_repeat_frequency(&changes), -300);
+///
+/// let changes: Vec<i32> = generator.without_repeat(50).unwrap();
+/// assert!(!one::can_repeat(&changes));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Generator {
+        // Xorshift gets stuck on zero
+        let state = seed ^ 0x9e37_79b9_7f4a_7c15;
+
+        Generator { state: if state == 0 { 1 } else { state } }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        self.state
+    }
+
+    /// Returns a number from `low` to `high`, inclusive.
+    pub fn between(&mut self, low: i128, high: i128) -> i128 {
+        low + (u128::from(self.next()) % (high - low + 1) as u128) as i128
+    }
+
+    /// Returns one of the given items, which must not be empty.
+    pub fn choose<U: Copy>(&mut self, items: &[U]) -> U {
+        items[self.between(0, items.len() as i128 - 1) as usize]
+    }
+
+    /// Builds a list of the given number of changes whose first repeated
+    /// frequency is the given value, reached again at the given pass and
+    /// index, both counting from zero.
+    ///
+    /// Returns `None` if there is no such list, such as for a repeat on the
+    /// first change of the first pass, a repeat of zero after the second
+    /// pass, or a repeat on the last change of pass `p` that is not a
+    /// multiple of `p + 1`. A few lists that do exist are not built either:
+    /// repeats of zero after the first pass, and repeats on the last change
+    /// that would need a drift smaller than the number of changes. `None` is
+    /// also returned if the frequencies would not fit in their type.
+    pub fn with_repeat<T: Frequency>(&mut self, len: usize, pass: u128, index: usize, value: T) -> Option<Vec<T>> {
+        if index >= len {
+            return None;
+        }
+
+        let value = value.to_i128();
+        let pass = i128::try_from(pass).ok()?;
+        let sums = if pass == 0 {
+            self.first_pass_repeat(len, index, value)?
+        } else {
+            self.later_pass_repeat(len, pass, index, value)?
+        };
+
+        // Each frequency moves steadily by the drift, so if they fit at the
+        // start and at the end of the search, they fit all the way through
+        let furthest = sums[len - 1].checked_mul(pass)?;
+        for &sum in &sums {
+            T::from_i128(sum)?;
+            T::from_i128(sum.checked_add(furthest)?)?;
+        }
+
+        changes_from_sums(&sums)
+    }
+
+    /// Builds the frequencies of a first pass in which the value is reached
+    /// at the given index for the second time.
+    fn first_pass_repeat(&mut self, len: usize, index: usize, value: i128) -> Option<Vec<i128>> {
+        if index == 0 {
+            return None;
+        }
+
+        let spread = 2 * len as i128;
+        let first = self.between(0, index as i128 - 1) as usize;
+        let mut seen = HashSet::new();
+        seen.insert(value);
+
+        let sums = (0..len)
+            .map(|i| {
+                if i == first || i == index {
+                    return value;
+                }
+
+                loop {
+                    let sum = value + self.between(-spread, spread);
+
+                    // Only the frequencies before the repeat need to differ
+                    if i > index || seen.insert(sum) {
+                        return sum;
+                    }
+                }
+            })
+            .collect();
+
+        Some(sums)
+    }
+
+    /// Builds the frequencies of a first pass that reach the value at the
+    /// given index of the given pass, after it was first reached on the
+    /// first pass.
+    ///
+    /// The value's position and one other are made congruent modulo the
+    /// drift, with the other one the given number of passes ahead, and every
+    /// other position is left alone in its own congruence class. Only the
+    /// pair ever repeats then, and it does so on the given pass.
+    fn later_pass_repeat(&mut self, len: usize, pass: i128, index: usize, value: i128) -> Option<Vec<i128>> {
+        let last = len - 1;
+        let others: Vec<usize> = (0..len).filter(|&i| i != index && i != last).collect();
+
+        // The last position always has the drift as its frequency, which is
+        // congruent to zero
+        if value == 0 {
+            return None;
+        }
+        let (first, drift) = if index == last {
+            if others.is_empty() || value % (pass + 1) != 0 {
+                return None;
+            }
+
+            (self.choose(&others), value / (pass + 1))
+        } else if others.is_empty() {
+            (last, value)
+        } else {
+            let mut modulus = self.between(len as i128 - 1, 2 * len as i128).max(2);
+            while value % modulus == 0 {
+                modulus += 1;
+            }
+
+            (self.choose(&others), modulus * self.choose(&[-1, 1]))
+        };
+
+        let modulus = drift.abs();
+        if modulus < (len as i128 - 1).max(1) {
+            return None;
+        }
+
+        let mut sums = vec![0; len];
+        sums[last] = drift;
+        sums[first] = value;
+        sums[index] = value.checked_sub(pass.checked_mul(drift)?)?;
+
+        let mut used = HashSet::new();
+        used.insert(0);
+        used.insert(value.rem_euclid(modulus));
+        for i in (0..len).filter(|&i| i != index && i != first && i != last) {
+            let residue = loop {
+                let residue = self.between(1, modulus - 1);
+                if used.insert(residue) {
+                    break residue;
+                }
+            };
+
+            sums[i] = residue + modulus * self.between(-1, 1);
+        }
+
+        Some(sums)
+    }
+
+    /// Builds a list of the given number of changes in which no frequency
+    /// is ever reached twice, or `None` if it would not fit in the
+    /// frequency type.
+    ///
+    /// Every frequency of the first pass is left alone in its own
+    /// congruence class modulo a non-zero drift.
+    pub fn without_repeat<T: Frequency>(&mut self, len: usize) -> Option<Vec<T>> {
+        if len == 0 {
+            return Some(Vec::new());
+        }
+
+        let modulus = self.between(len as i128, 2 * len as i128);
+        let mut sums = vec![0; len];
+        sums[len - 1] = modulus * self.choose(&[-1, 1]);
+
+        let mut used = HashSet::new();
+        for sum in sums.iter_mut().take(len - 1) {
+            let residue = loop {
+                let residue = self.between(1, modulus - 1);
+                if used.insert(residue) {
+                    break residue;
+                }
+            };
+
+            *sum = residue + modulus * self.between(-1, 1);
+        }
+
+        changes_from_sums(&sums)
+    }
+}
+
+/// Works out the changes that give the frequencies of the first pass, or
+/// `None` if they do not fit in the frequency type.
+fn changes_from_sums<T: Frequency>(sums: &[i128]) -> Option<Vec<T>> {
+    let mut previous = 0;
+
+    sums.iter()
+        .map(|&sum| {
+            let change = sum.checked_sub(previous)?;
+            previous = sum;
+
+            T::from_i128(change)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::LinkedList;
+    use std::fs;
+
+    use client;
+
+    /// Returns a list of one to eight changes from -10 to 10, small enough
+    /// that simulating them finishes quickly.
+    fn random_changes<T: Frequency>(generator: &mut Generator) -> Vec<T> {
+        let len = generator.between(1, 8);
+
+        (0..len).map(|_| T::from_i128(generator.between(-10, 10)).unwrap()).collect()
+    }
+
+    #[test]
+    fn sum_changes_it_sums_zero_changes() {
+        let changes = LinkedList::new();
+        let res = sum_changes(&changes);
+
+        assert_eq!(res, 0);
+    }
+
+    #[test]
+    fn sum_changes_it_sums_one_change() {
+        let mut changes = LinkedList::new();
+
+        changes.push_back(2);
+
+        let res = sum_changes(&changes);
+
+        assert_eq!(res, 2);
+    }
+
+    #[test]
+    fn sum_changes_it_sums_multiple_changes() {
+        let mut changes = LinkedList::new();
+
+        changes.push_back(2);
+        changes.push_back(1);
+        changes.push_back(-4);
+        changes.push_back(5);
+
+        let res = sum_changes(&changes);
+
+        assert_eq!(res, 4);
+    }
+
+    #[test]
+    fn sum_it_works_with_wide_integers() {
+        let changes: Vec<i128> = vec![i128::from(i64::MAX), 1];
+
+        let res = sum(&changes);
+
+        assert_eq!(res, i128::from(i64::MAX) + 1);
+    }
+
+    #[test]
+    fn sum_it_works_with_owned_iterators() {
+        let res = sum((1..5).map(|c: i64| -c));
+
+        assert_eq!(res, -10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The frequency overflowed at change 1 of pass 0")]
+    fn sum_changes_it_panics_on_overflow() {
+        let mut changes = LinkedList::new();
+
+        changes.push_back(1);
+        changes.push_back(i32::MAX);
+        changes.push_back(-5);
+
+        sum_changes(&changes);
+    }
+
+    #[test]
+    #[should_panic(expected = "The frequency overflowed at change 2 of pass 0")]
+    fn prefix_sums_it_panics_on_overflow() {
+        prefix_sums(&[-1, -1, i32::MIN]);
+    }
+
+    #[test]
+    fn first_repeat_it_works_with_slices() {
+        let changes = [3i64, 3, 4, -2, -4];
+
+        let res = first_repeat(&changes[..]);
+
+        assert_eq!(res, 10);
+    }
+
+    #[test]
+    fn can_repeat_it_is_false_for_no_changes() {
+        let changes: [i32; 0] = [];
+
+        assert!(!can_repeat(&changes));
+    }
+
+    #[test]
+    fn can_repeat_it_is_true_for_zero_drift() {
+        // The prefix sums are distinct, but the second pass repeats them
+        let changes = [1, 2, -3];
+
+        assert!(can_repeat(&changes));
+    }
+
+    #[test]
+    fn can_repeat_it_is_true_for_congruent_prefix_sums() {
+        // Prefix sums 5, 2, 3 with a drift of 3, so 5 + 3k meets 2 + 3k
+        let changes = [5, -3, 1];
+
+        assert!(can_repeat(&changes));
+    }
+
+    #[test]
+    fn can_repeat_it_is_false_for_distinct_residues() {
+        // Prefix sums 1, 2, -4 are distinct modulo a drift of -4
+        let changes = [1, 1, -6];
+
+        assert_eq!(prefix_sums(&changes), vec![1, 2, -4]);
+        assert!(!can_repeat(&changes));
+    }
+
+    #[test]
+    fn find_first_repeat_it_finds_repeats() {
+        let changes = [5, -3, 1];
+
+        let res = find_first_repeat(&changes);
+
+        assert_eq!(res, Ok(5));
+    }
+
+    #[test]
+    fn find_first_repeat_it_halts_without_repeats() {
+        let changes = [1i64, 1, -6];
+
+        let res = find_first_repeat(&changes);
+
+        assert_eq!(res, Err(RepeatError::NoRepeat));
+    }
+
+    #[test]
+    fn analytic_first_repeat_it_finds_repeats_within_first_pass() {
+        let sums = prefix_sums(&[2, 1, -1, 5]);
+
+        assert_eq!(analytic_first_repeat(&sums), Ok(2));
+    }
+
+    #[test]
+    fn analytic_first_repeat_it_finds_repeats_with_zero_drift() {
+        let sums = prefix_sums(&[1, -1]);
+
+        assert_eq!(analytic_first_repeat(&sums), Ok(1));
+    }
+
+    #[test]
+    fn analytic_first_repeat_it_finds_repeats_with_negative_drift() {
+        let changes = [-6, 3, 8, -6];
+
+        let expected = first_repeat(&changes);
+        let actual = analytic_first_repeat(&prefix_sums(&changes));
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn analytic_first_repeat_it_takes_the_earliest_catch_up() {
+        // 10 catches up with 100 after 9 passes, but 1 catches up with 21
+        // after 2 passes
+        let changes = [1, 99, -79, -11];
+
+        let res = analytic_first_repeat(&prefix_sums(&changes));
+
+        assert_eq!(res, Ok(21));
+        assert_eq!(first_repeat(&changes), 21);
+    }
+
+    #[test]
+    fn analytic_first_repeat_it_handles_many_passes() {
+        let changes = [1_000_000_000i64, -999_999_999];
+
+        let res = analytic_first_repeat(&prefix_sums(&changes));
+
+        assert_eq!(res, Ok(1_000_000_000));
+    }
+
+    #[test]
+    fn analytic_first_repeat_it_reports_no_repeat() {
+        let changes: [i32; 0] = [];
+
+        assert_eq!(analytic_first_repeat(&prefix_sums(&[1, 1, -6])), Err(RepeatError::NoRepeat));
+        assert_eq!(analytic_first_repeat(&prefix_sums(&changes)), Err(RepeatError::NoRepeat));
+    }
+
+    #[test]
+    fn find_first_repeat_with_strategies_agree() {
+        // Small random change lists, so that the simulation finishes
+        let mut generator = Generator::new(0x2018);
+        for _ in 0..2000 {
+            let changes: Vec<i32> = random_changes(&mut generator);
+
+            let simulated = find_first_repeat_with(&changes, Strategy::Simulation);
+            let analytic = find_first_repeat_with(&changes, Strategy::Analytic);
+
+            assert_eq!(simulated, analytic, "changes: {:?}", changes);
+        }
+    }
+
+    #[test]
+    fn find_repeat_report_it_reports_repeats_within_first_pass() {
+        let expected = RepeatReport {
+            frequency: 2,
+            pass: 0,
+            index: 3,
+            first_pass: 0,
+            first_index: 0,
+            changes_applied: 4,
+        };
+
+        for &strategy in [Strategy::Simulation, Strategy::Analytic].iter() {
+            assert_eq!(find_repeat_report(&[2, 1, -2, 1], strategy), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn find_repeat_report_it_reports_no_repeat() {
+        for &strategy in [Strategy::Simulation, Strategy::Analytic].iter() {
+            assert_eq!(find_repeat_report(&[1, 1, 1], strategy), Err(RepeatError::NoRepeat));
+        }
+    }
+
+    #[test]
+    fn find_repeat_report_strategies_agree() {
+        let mut generator = Generator::new(0x34);
+        for _ in 0..2000 {
+            let changes: Vec<i64> = random_changes(&mut generator);
+
+            let simulated = find_repeat_report(&changes, Strategy::Simulation);
+            let analytic = find_repeat_report(&changes, Strategy::Analytic);
+
+            assert_eq!(simulated, analytic, "changes: {:?}", changes);
+        }
+    }
+
+    #[test]
+    fn parse_changes_it_reads_newline_separated_changes() {
+        let actual: Result<Vec<i32>, _> = parse_changes("+1\n-2\n+3\n+1\n");
+
+        assert_eq!(actual, Ok(vec![1, -2, 3, 1]));
+    }
+
+    #[test]
+    fn parse_changes_it_reads_mixed_separators() {
+        let actual: Result<Vec<i64>, _> = parse_changes("+1, -2,\n+3\n\n");
+
+        assert_eq!(actual, Ok(vec![1, -2, 3]));
+    }
+
+    #[test]
+    fn parse_changes_it_locates_bad_tokens() {
+        let actual: Result<Vec<i32>, _> = parse_changes("+1\r\n# Comment, +x\r\n+2,  +3, 4a\r\n");
+
+        let expected = ParseError { line: 3, column: 10, token: "4a".to_string() };
+
+        assert_eq!(actual, Err(expected));
+    }
+
+    #[test]
+    fn parse_changes_it_rejects_empty_fields() {
+        let actual: Result<Vec<i32>, _> = parse_changes("+1,,+2");
+
+        let expected = ParseError { line: 1, column: 4, token: String::new() };
+
+        assert_eq!(actual, Err(expected));
+    }
+
+    #[test]
+    fn parse_changes_it_rejects_overflowing_changes() {
+        let actual: Result<Vec<i32>, _> = parse_changes("+1\n+3000000000\n");
+
+        assert_eq!(actual.unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn checked_sum_it_sums_zero_changes() {
+        let changes: [i32; 0] = [];
+
+        assert_eq!(checked_sum(&changes), Ok(0));
+    }
+
+    #[test]
+    fn checked_sum_it_reports_underflow() {
+        let changes = [-5, i32::MIN + 10, -6, 100];
+
+        assert_eq!(checked_sum(&changes), Err(Overflow { pass: 0, index: 2 }));
+    }
+
+    #[test]
+    fn evaluate_reader_it_matches_parse_changes() {
+        let input = "+7, -2,\n# Calibration\r\n\r\n+3 # Third\n-10\n*3, %7\n=4, -1";
+
+        let expected = evaluate(&parse_changes::<Change<i32>>(input).unwrap()).unwrap();
+
+        assert_eq!(evaluate_reader::<i32, _>(input.as_bytes()).unwrap(), expected);
+    }
+
+    #[test]
+    fn evaluate_reader_it_reports_bad_tokens_and_overflow() {
+        match evaluate_reader::<i32, _>("+1\n+2, x3\n".as_bytes()) {
+            Err(SumError::Parse(e)) => assert_eq!((e.line, e.column), (2, 5)),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        match evaluate_reader::<i32, _>("-5\n-2147483640, -6\n+100\n".as_bytes()) {
+            Err(SumError::Overflow(overflow)) => assert_eq!(overflow, Overflow { pass: 0, index: 2 }),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn split_lines_it_splits_at_line_ends() {
+        let bytes = b"+1\n-22\n+333\n-4444\n";
+
+        for count in 1..8 {
+            let chunks = split_lines(bytes, count);
+
+            assert!(chunks.len() <= count);
+            assert_eq!(chunks.concat(), bytes.to_vec());
+            assert!(chunks.iter().all(|chunk| chunk.ends_with(b"\n")));
+        }
+    }
+
+    #[test]
+    fn evaluate_file_parallel_it_matches_evaluate_reader() {
+        let dir = client::stub::temp_dir("evaluate_file_parallel");
+        let inputs = [
+            "",
+            "+1, -2\n+3\n+1",
+            "-5\n+2147483647\n+1\n-1\n",
+            "+2147483647\n-1\n+1\n+1\n",
+            "+1\n+2\n# Comment\n+x\n+3\n",
+            "+1\n+2\n*3\n-4\n%5\n+6\n=7\n+8\n",
+            "+1073741824\n*2\n+1\n",
+        ];
+
+        for (i, input) in inputs.iter().enumerate() {
+            let path = dir.join(format!("{}.txt", i));
+            fs::write(&path, input).unwrap();
+
+            let expected = format!("{:?}", evaluate_reader::<i32, _>(input.as_bytes()));
+            for threads in 1..6 {
+                let actual = format!("{:?}", evaluate_file_parallel::<i32>(&path, threads));
+
+                assert_eq!(actual, expected, "{:?} on {} threads", input, threads);
+            }
+        }
+    }
+
+    #[test]
+    fn find_first_repeat_with_it_reports_first_pass_overflow() {
+        let changes = [i32::MAX, 1, -1];
+
+        for &strategy in [Strategy::Simulation, Strategy::Analytic].iter() {
+            let expected = Err(RepeatError::Overflow(Overflow { pass: 0, index: 1 }));
+
+            assert_eq!(find_first_repeat_with(&changes, strategy), expected);
+        }
+    }
+
+    #[test]
+    fn find_first_repeat_with_it_reports_simulation_overflow() {
+        let changes = [i32::MAX, -(i32::MAX - 1)];
+
+        let simulated = find_first_repeat_with(&changes, Strategy::Simulation);
+        let analytic = find_first_repeat_with(&changes, Strategy::Analytic);
+
+        assert_eq!(simulated, Err(RepeatError::Overflow(Overflow { pass: 1, index: 0 })));
+        assert_eq!(analytic, Ok(i32::MAX));
+    }
+
+    #[test]
+    fn part_two_with_it_uses_wider_types() {
+        let input = "+2000000000\n+2000000000\n-4000000000\n";
+
+        assert_eq!(part_one_with("+2000000000\n+2000000000\n", Width::I64), Ok("4000000000".to_string()));
+        assert_eq!(part_two_with(input, Strategy::Simulation, Width::I64), Ok("2000000000".to_string()));
+        assert_eq!(part_two_with(input, Strategy::Analytic, Width::I128), Ok("2000000000".to_string()));
+    }
+
+    #[test]
+    fn frequency_walk_it_composes_with_iterator_adapters() {
+        let passes: Vec<i32> = FrequencyWalk::new(vec![3, -1])
+            .take_while(|&(pass, _, _, _)| pass < 3)
+            .filter(|&(_, index, _, _)| index == 1)
+            .map(|(_, _, _, frequency)| frequency)
+            .collect();
+
+        assert_eq!(passes, vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn frequency_walk_it_ends_without_changes() {
+        let mut walk = FrequencyWalk::new(Vec::<i64>::new());
+
+        assert_eq!(walk.next(), None);
+        assert_eq!(walk.overflow(), None);
+    }
+
+    #[test]
+    fn frequency_walk_it_ends_on_overflow() {
+        let mut walk = FrequencyWalk::new(&[i32::MAX / 2, 1]);
+
+        assert_eq!(walk.by_ref().count(), 3);
+        assert_eq!(walk.overflow(), Some(Overflow { pass: 1, index: 1 }));
+        assert_eq!(walk.next(), None);
+    }
+
+    #[test]
+    fn first_reached_times_it_matches_counting_every_visit() {
+        let changes = [7, 7, -2, -7, -4];
+
+        // Every position is congruent modulo a drift of 1, so up to 5 times
+        // is possible
+        for times in 1..7 {
+            let mut counts = HashMap::new();
+            let expected = FrequencyWalk::new(&changes)
+                .take(1000)
+                .map(|(_, _, _, frequency)| frequency)
+                .find(|&frequency| {
+                    let count = counts.entry(frequency).or_insert(0);
+                    *count += 1;
+
+                    *count >= times
+                });
+
+            assert_eq!(first_reached_times(&changes, times).ok(), expected, "times: {}", times);
+        }
+    }
+
+    #[test]
+    fn first_reached_times_it_handles_zero_drift() {
+        assert_eq!(first_reached_times(&[1, -1], 3), Ok(1));
+    }
+
+    #[test]
+    fn first_reached_times_it_counts_equal_positions_separately() {
+        // The two positions at 2 have the same residue, but no others do, so
+        // no frequency is reached more than twice
+        let changes = [2, 0, 5];
+
+        assert_eq!(first_reached_times(&changes, 2), Ok(2));
+        assert_eq!(first_reached_times(&changes, 3), Err(RepeatError::NoRepeat));
+    }
+
+    #[test]
+    fn first_repeats_it_starts_with_the_first_repeat() {
+        let changes = [7, 7, -2, -7, -4];
+
+        let repeats = first_repeats(&changes, 4).unwrap();
+
+        assert_eq!(repeats.len(), 4);
+        assert_eq!(repeats[0], first_repeat(&changes));
+    }
+
+    #[test]
+    fn visit_counts_it_reports_overflow_within_the_passes() {
+        let changes = [i32::MAX];
+
+        assert_eq!(visit_counts(&changes, 1).unwrap().len(), 1);
+        assert_eq!(visit_counts(&changes, 2), Err(Overflow { pass: 1, index: 0 }));
+    }
+
+    #[test]
+    fn part_query_it_puts_each_value_on_its_own_line() {
+        let input = "+3\n+3\n+4\n-2\n-4\n";
+
+        assert_eq!(part_query(input, Query::ReachedTimes(2), Width::I32), Ok("10".to_string()));
+        assert_eq!(part_query(input, Query::Repeats(3), Width::I64), Ok("10\n8\n14".to_string()));
+        assert_eq!(part_query("+1\n-1\n", Query::Visits(2), Width::I128), Ok("0: 2\n1: 2".to_string()));
+    }
+
+    #[test]
+    fn drift_report_it_reports_inputs_that_never_repeat() {
+        let report = drift_report(&[1, 1, -6]).unwrap();
+
+        assert_eq!(report.drift, -4);
+        assert_eq!(report.spread, 6);
+        assert_eq!(report.passes_needed, None);
+    }
+
+    #[test]
+    fn drift_report_it_reports_no_drift_without_changes() {
+        let report = part_drift_report("", Width::I32).unwrap();
+
+        assert_eq!((report.drift, report.min, report.max, report.spread), (0, 0, 0, 0));
+        assert_eq!(report.passes_needed, None);
+        assert_eq!(report.chart(80, 15), "");
+    }
+
+    #[test]
+    fn drift_report_it_matches_the_simulation() {
+        let changes = [7, 7, -2, -7, -4];
+
+        let report = drift_report(&changes).unwrap();
+        let repeat = find_repeat_report(&changes, Strategy::Simulation).unwrap();
+
+        assert_eq!(report.passes_needed, Some(repeat.pass + 1));
+    }
+
+    #[test]
+    fn chart_it_fits_many_changes_into_the_columns() {
+        let changes: Vec<i64> = (0..100).map(|i| if i < 50 { 1 } else { -1 }).collect();
+
+        let chart = drift_report(changes).unwrap().chart(12, 4);
+        let lines: Vec<&str> = chart.lines().collect();
+
+        assert_eq!(lines.len(), 6);
+        assert!(lines.iter().all(|line| line.len() <= 12));
+        assert_eq!(lines[0], "50|   ***");
+        assert_eq!(lines[4], "  +---------");
+        assert_eq!(lines[5], "   0     100");
+    }
+
+    #[test]
+    fn chart_it_draws_flat_frequencies() {
+        let chart = drift_report(&[0, 0, 0]).unwrap().chart(80, 3);
+
+        assert_eq!(chart, "0|\n |***\n0|\n +---\n  0 3\n");
+    }
+
+    #[test]
+    fn visited_set_it_hashes_frequencies_outside_the_range() {
+        let mut visited = VisitedSet::with_range(-3, 3).unwrap();
+
+        assert!(visited.insert(-3));
+        assert!(visited.insert(3));
+        assert!(visited.insert(4));
+        assert!(visited.insert(-100));
+        assert!(!visited.insert(-3));
+        assert!(!visited.insert(4));
+        assert!(!visited.insert(-100));
+    }
+
+    #[test]
+    fn visited_set_it_handles_the_whole_i128_range() {
+        let mut visited = VisitedSet::with_range(-1, 1).unwrap();
+
+        assert!(visited.insert(i128::MIN));
+        assert!(visited.insert(i128::MAX));
+        assert!(!visited.insert(i128::MIN));
+    }
+
+    #[test]
+    fn visited_set_it_rejects_ranges_it_cannot_count() {
+        assert!(VisitedSet::with_range(3, -3).is_none());
+        assert!(VisitedSet::with_range(i128::MIN, i128::MAX).is_none());
+        assert!(VisitedSet::with_range(i64::MIN, i64::MAX).is_none());
+        assert!(VisitedSet::with_range(0, BITSET_LIMIT as i64).is_none());
+    }
+
+    #[test]
+    fn for_changes_it_uses_a_bitset_for_small_ranges() {
+        assert!(VisitedSet::for_changes(&[7, 7, -2, -7, -4]).is_bitset());
+        assert!(VisitedSet::for_changes(&[-6, 3, 8, -6]).is_bitset());
+    }
+
+    #[test]
+    fn for_changes_it_hashes_large_ranges() {
+        assert!(!VisitedSet::for_changes(&[1_000_000_000i64, -999_999_999]).is_bitset());
+        assert!(!VisitedSet::for_changes(&[i128::MAX, i128::MIN + 1]).is_bitset());
+        assert!(!VisitedSet::for_changes(&[-(1i128 << 126), 1 << 126, 1]).is_bitset());
+    }
+
+    #[test]
+    fn for_changes_it_hashes_when_there_is_no_repeat() {
+        assert!(!VisitedSet::for_changes(&[1, 1, -6]).is_bitset());
+        assert!(!VisitedSet::<i32>::for_changes(&[]).is_bitset());
+    }
+
+    #[test]
+    fn first_repeat_using_sets_agree() {
+        let mut generator = Generator::new(0x40);
+        for _ in 0..2000 {
+            let changes: Vec<i32> = random_changes(&mut generator);
+
+            if !can_repeat(&changes) {
+                continue;
+            }
+
+            let mut bounded = VisitedSet::for_changes(&changes);
+            let repeat = FrequencyWalk::new(&changes)
+                .map(|(_, _, _, frequency)| frequency)
+                .find(|&frequency| !bounded.insert(frequency));
+
+            // Every frequency up to the repeat should have been within the
+            // range of the bitset
+            assert!(bounded.is_bitset());
+            assert!(bounded.outside.is_empty(), "changes: {:?}", changes);
+            assert_eq!(repeat, Some(first_repeat_using(&changes, VisitedSet::hashed())));
+        }
+    }
+
+    #[test]
+    fn first_time_reaching_it_matches_the_walk() {
+        let changes = [7, 7, -2, -7, -4];
+
+        for target in -5..40 {
+            let expected = FrequencyWalk::new(&changes)
+                .take(200)
+                .find(|&(_, _, _, frequency)| frequency == target)
+                .map(|(pass, index, _, _)| (pass, index));
+
+            assert_eq!(first_time_reaching(&changes, target), Ok(expected), "target: {}", target);
+        }
+    }
+
+    #[test]
+    fn first_time_reaching_it_handles_negative_drift() {
+        let changes = [-6, 3, 8, -6];
+
+        assert_eq!(first_time_reaching(&changes, -13), Ok(Some((7, 0))));
+        assert_eq!(first_time_reaching(&changes, 6), Ok(None));
+    }
+
+    #[test]
+    fn first_time_reaching_it_handles_zero_drift() {
+        assert_eq!(first_time_reaching(&[2, -2], 0), Ok(Some((0, 1))));
+        assert_eq!(first_time_reaching(&[2, -2], 1), Ok(None));
+        assert_eq!(first_time_reaching(Vec::<i32>::new(), 0), Ok(None));
+    }
+
+    #[test]
+    fn first_time_reaching_it_handles_distant_targets() {
+        // The distance from i128::MIN to i128::MAX only fits in a u128
+        let changes = [i128::MIN, i128::MAX, 2];
+
+        assert_eq!(first_time_reaching(&changes, i128::MIN), Ok(Some((0, 0))));
+        assert_eq!(first_time_reaching(&changes, i128::MAX), Ok(Some((i128::MAX as u128 - 1, 2))));
+        assert_eq!(first_time_reaching(&[-1i128], i128::MIN), Ok(Some((i128::MAX as u128, 0))));
+    }
+
+    #[test]
+    fn part_query_it_answers_reach_queries() {
+        assert_eq!(part_query("+1\n+1\n", Query::Reach(5), Width::I32), Ok("pass 2, change 0".to_string()));
+        assert_eq!(part_query("+1\n+1\n", Query::Reach(i128::MAX), Width::I32), Ok("never".to_string()));
+    }
+
+    #[test]
+    fn what_if_it_matches_editing_from_scratch() {
+        let changes = [7, 7, -2, -7, -4];
+
+        let report = what_if(&changes).unwrap();
+
+        assert_eq!(report.edits.len(), 10);
+        for w in &report.edits {
+            let mut edited = changes.to_vec();
+            match w.edit {
+                Edit::Negate => edited[w.index] = -edited[w.index],
+                Edit::Remove => { edited.remove(w.index); }
+            }
+
+            assert_eq!(w.sum, sum(&edited));
+            assert_eq!(w.first_repeat, find_first_repeat(&edited).ok(), "{:?}", w);
+        }
+    }
+
+    #[test]
+    fn what_if_it_ranks_starting_or_stopping_repeats_first() {
+        // Editing the -2 leaves a non-zero drift that no two frequencies are
+        // congruent modulo, so they never repeat
+        let report = what_if(&[1, 1, -2]).unwrap();
+
+        assert_eq!(report.first_repeat, Some(1));
+        for w in &report.edits[..2] {
+            assert_eq!((w.index, w.first_repeat, w.repeat_shift), (2, None, None));
+        }
+        assert!(report.edits[2..].iter().all(|w| w.repeat_shift.is_some()));
+    }
+
+    #[test]
+    fn what_if_it_handles_a_single_change() {
+        let report = what_if(&[5]).unwrap();
+
+        let removed = report.edits.iter().find(|w| w.edit == Edit::Remove).unwrap();
+
+        assert_eq!((removed.sum, removed.first_repeat), (0, None));
+        assert_eq!(removed.repeat_shift, Some(0));
+    }
+
+    #[test]
+    fn what_if_it_reports_overflow() {
+        let changes = [i32::MIN + 1, 1];
+
+        // Negating the first change makes the second frequency -i32::MIN
+        assert_eq!(what_if(&changes), Err(Overflow { pass: 0, index: 1 }));
+    }
+
+    #[test]
+    fn change_from_str_it_rejects_bad_operations() {
+        assert_eq!("-3".parse::<Change<i32>>(), Ok(Change::Add(-3)));
+        assert_eq!("*-3".parse::<Change<i32>>(), Ok(Change::Mul(-3)));
+
+        for token in ["%0", "*", "=x", "/2", "+*2"].iter() {
+            assert_eq!(token.parse::<Change<i32>>(), Err(()), "{}", token);
+        }
+    }
+
+    #[test]
+    fn evaluate_it_takes_non_negative_remainders() {
+        assert_eq!(evaluate(&[Change::Add(-7), Change::Mod(3)]), Ok(2));
+        assert_eq!(evaluate(&[Change::Add(-7), Change::Mod(-3)]), Ok(2));
+        assert_eq!(
+            evaluate(&[Change::Set(i32::MIN), Change::Mod(-1)]),
+            Err(Overflow { pass: 0, index: 1 })
+        );
+    }
+
+    #[test]
+    fn program_repeat_report_it_matches_additive_search() {
+        let deltas = [3, 3, 4, -2, -4];
+        let changes: Vec<_> = deltas.iter().map(|&n| Change::Add(n)).collect();
+
+        for &strategy in [Strategy::Simulation, Strategy::Analytic].iter() {
+            assert_eq!(
+                program_repeat_report(&changes, strategy),
+                find_repeat_report(&deltas, strategy)
+            );
+        }
+        assert_eq!(
+            program_repeat_report::<i32>(&[], Strategy::Simulation),
+            Err(RepeatError::NoRepeat)
+        );
+    }
+
+    #[test]
+    fn program_repeat_report_it_handles_resets() {
+        let report = program_repeat_report(&[Change::Add(1), Change::Set(0)], Strategy::Analytic)
+            .unwrap();
+
+        assert_eq!(report.frequency, 1);
+        assert_eq!((report.pass, report.index), (1, 0));
+        assert_eq!((report.first_pass, report.first_index), (0, 0));
+    }
+
+    #[test]
+    fn program_repeat_report_it_reports_overflow() {
+        // 1, 2, 3, 6, 7, 14, ... never comes back down
+        let actual = program_repeat_report(&[Change::Add(1), Change::Mul(2)], Strategy::Simulation);
+
+        assert_eq!(actual, Err(RepeatError::Overflow(Overflow { pass: 30, index: 1 })));
+    }
+
+    #[test]
+    fn part_two_with_it_accepts_operations() {
+        assert_eq!(part_one_with("+5\n*2\n%7\n", Width::I32), Ok("3".to_string()));
+        assert_eq!(part_two_with("+5, *2, %7", Strategy::Analytic, Width::I64), Ok("5".to_string()));
+    }
+
+    #[test]
+    fn part_query_it_rejects_operations_other_than_additions() {
+        let unsupported = PartError::Unsupported { index: 1, change: "*2".to_string() };
+
+        assert_eq!(part_query("+5\n*2\n", Query::Repeats(1), Width::I32), Err(unsupported.clone()));
+        assert_eq!(part_what_if("+5\n*2\n", Width::I32).map(|_| ()), Err(unsupported.clone()));
+        assert_eq!(part_drift_report("+5\n*2\n", Width::I32).map(|_| ()), Err(unsupported.clone()));
+        assert_eq!(
+            unsupported.to_string(),
+            "Change 1 (*2) is not supported in this mode, which only handles additions"
+        );
+    }
+
+    #[test]
+    fn part_two_with_it_returns_errors() {
+        let parse_error = ParseError { line: 2, column: 1, token: "x".to_string() };
+
+        assert_eq!(part_one_with("+1\nx\n", Width::I32), Err(PartError::Parse(parse_error.clone())));
+        assert_eq!(part_two_with("+1\nx\n", Strategy::Simulation, Width::I32), Err(PartError::Parse(parse_error)));
+        assert_eq!(part_two_with("+1\n+1\n", Strategy::Analytic, Width::I32), Err(PartError::NoRepeat));
+        assert_eq!(
+            part_one_with("+2147483647\n+1\n", Width::I32),
+            Err(PartError::Overflow(Overflow { pass: 0, index: 1 }))
+        );
+    }
+
+    #[test]
+    fn merge_devices_it_skips_devices_that_run_out() {
+        let inputs = ["+1", "", "-1\n-2\n-3", "+5, +6"];
+
+        let merged: Vec<i32> = merge_devices(&inputs, Merge::RoundRobin).unwrap();
+
+        assert_eq!(merged, vec![1, -1, 5, -2, 6, -3]);
+    }
+
+    #[test]
+    fn merge_devices_it_locates_bad_tokens_by_device() {
+        let missing = merge_devices::<i32, _>(&["[1] +1", "# Late\n[2] +1\n  +3"], Merge::Timestamp);
+        let invalid = merge_devices::<i32, _>(&["[1] +1, x"], Merge::Timestamp);
+
+        assert_eq!(missing, Err(DeviceError {
+            device: 1,
+            error: ParseError { line: 3, column: 3, token: "+3".to_string() },
+        }));
+        assert_eq!(invalid, Err(DeviceError {
+            device: 0,
+            error: ParseError { line: 1, column: 9, token: "x".to_string() },
+        }));
+    }
+
+    #[test]
+    fn merge_report_it_reports_no_repeat() {
+        let report = merge_report(&["+1", "+1"], Merge::RoundRobin, Width::I32).unwrap();
+
+        assert_eq!(report, MergeReport { changes: 2, frequency: 2, first_repeat: None });
+    }
+
+    #[test]
+    fn merge_report_it_uses_the_given_width() {
+        let inputs = ["+2000000000\n-4000000000", "+2000000000"];
+
+        assert_eq!(
+            merge_report(&inputs, Merge::RoundRobin, Width::I32),
+            Err(MergeError::Device(DeviceError {
+                device: 0,
+                error: ParseError { line: 2, column: 1, token: "-4000000000".to_string() },
+            }))
+        );
+        assert_eq!(
+            merge_report(&["+2000000000", "+2000000000"], Merge::RoundRobin, Width::I32),
+            Err(MergeError::Overflow(Overflow { pass: 0, index: 1 }))
+        );
+
+        let report = merge_report(&inputs, Merge::RoundRobin, Width::I64).unwrap();
+        assert_eq!(report, MergeReport { changes: 3, frequency: 0, first_repeat: Some(2000000000) });
+    }
+
+    #[test]
+    fn merge_report_it_accepts_operations() {
+        let report = merge_report(&["+5\n%7", "*2"], Merge::RoundRobin, Width::I32).unwrap();
+
+        // Merges into +5, *2, %7, as in the example for program_repeat_report
+        assert_eq!(report, MergeReport { changes: 3, frequency: 3, first_repeat: Some(5) });
+    }
+
+    #[test]
+    fn generator_it_plants_the_first_repeat() {
+        let cases = [
+            (2, 0, 1, 5),
+            (2, 3, 0, -7),
+            (2, 2, 1, 9),
+            (10, 0, 9, -4),
+            (10, 1, 0, 1),
+            (10, 4, 9, 50),
+            (100, 25, 40, 12345),
+            (1000, 3, 999, -4000),
+        ];
+
+        for seed in 0..20 {
+            let mut generator = Generator::new(seed);
+
+            for &(len, pass, index, value) in cases.iter() {
+                let changes: Vec<i32> = generator.with_repeat(len, pass, index, value)
+                    .unwrap_or_else(|| panic!("No list for {:?}", (len, pass, index, value)));
+                let report = find_repeat_report(&changes, Strategy::Simulation).unwrap();
+
+                assert_eq!(changes.len(), len);
+                assert_eq!((report.frequency, report.pass, report.index), (value, pass, index));
+                assert_eq!(get_first_repeat_frequency(&changes.iter().cloned().collect()), value);
+            }
+        }
+    }
+
+    #[test]
+    fn generator_it_builds_lists_that_never_repeat() {
+        let mut generator = Generator::new(7);
+
+        for len in 0..50 {
+            let changes: Vec<i64> = generator.without_repeat(len).unwrap();
+
+            assert_eq!(changes.len(), len);
+            assert_eq!(find_repeat_report(&changes, Strategy::Analytic), Err(RepeatError::NoRepeat));
+        }
+    }
+
+    #[test]
+    fn generator_it_refuses_impossible_repeats() {
+        let mut generator = Generator::new(1);
+
+        // The first change has nothing before it to repeat
+        assert_eq!(generator.with_repeat(5, 0, 0, 3), None::<Vec<i32>>);
+        assert_eq!(generator.with_repeat(5, 1, 5, 3), None::<Vec<i32>>);
+        // The last change of the third pass is always three times the drift
+        assert_eq!(generator.with_repeat(5, 2, 4, 10), None::<Vec<i32>>);
+        assert_eq!(generator.with_repeat(5, 2, 1, 0), None::<Vec<i32>>);
+        assert_eq!(generator.with_repeat(5, 1 << 40, 1, 3), None::<Vec<i32>>);
     }
 
     #[test]
@@ -0,0 +1,202 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::time::Duration;
+
+use config::Config;
+use solutions::{self, RunError, SOLUTIONS};
+
+/// The comment that marks the start of the generated results section.
+pub const START_MARKER: &str = "<!-- results:start -->";
+
+/// The comment that marks the end of the generated results section.
+pub const END_MARKER: &str = "<!-- results:end -->";
+
+/// The result of running one part of a day on its input.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Entry {
+    pub day: u32,
+    pub part: String,
+    /// The command that runs the part on its input.
+    pub command: String,
+    pub answer: Option<String>,
+    /// Why there is no answer, if there is none.
+    pub error: Option<String>,
+    pub seconds: f64,
+}
+
+/// Runs every implemented part on its configured input file.
+///
+/// Parts whose input file is missing or that fail to produce an answer are
+/// still included, with the reason in place of the answer.
+pub fn run_all(config: &Config) -> Vec<Entry> {
+    SOLUTIONS.iter()
+        .map(|solution| {
+            let path = config.input_path(solution.day);
+            let command = format!(
+                "cargo run -- day{} {} < {}",
+                solution.day, solution.part, path.display()
+            );
+
+            let result = fs::read_to_string(&path)
+                .map_err(|e| format!("Could not read {}: {}", path.display(), e))
+                .and_then(|input| {
+                    solutions::run(solution, input, config.timeout)
+                        .map_err(|e: RunError| e.to_string())
+                });
+
+            let (answer, error, elapsed) = match result {
+                Ok(answer) => (Some(answer.answer), None, answer.elapsed),
+                Err(e) => (None, Some(e), Duration::from_secs(0)),
+            };
+
+            Entry {
+                day: solution.day,
+                part: solution.part.to_string(),
+                command,
+                answer,
+                error,
+                seconds: duration_secs(elapsed),
+            }
+        })
+        .collect()
+}
+
+fn duration_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
+}
+
+/// Formats a runtime with a unit that suits its size.
+fn format_runtime(seconds: f64) -> String {
+    if seconds < 1.0 {
+        format!("{:.1} ms", seconds * 1000.0)
+    } else {
+        format!("{:.2} s", seconds)
+    }
+}
+
+/// Renders the given entries as a Markdown table.
+///
+/// ```
+/// use advent_of_code_2018::report::{self, Entry};
+///
+/// let entries = vec![Entry {
+///     day: 1,
+///     part: "one".to_string(),
+///     command: "cargo run -- day1 one < inputs/1.txt".to_string(),
+///     answer: Some("411".to_string()),
+///     error: None,
+///     seconds: 0.0012,
+/// }];
+///
+/// let table = report::render(&entries);
+///
+/// assert!(table.contains("| `cargo run -- day1 one < inputs/1.txt` | `411` | 1.2 ms |"));
+/// ```
+pub fn render(entries: &[Entry]) -> String {
+    let mut table = String::new();
+
+    table.push_str("| Command | Answer | Runtime |\n");
+    table.push_str("| --- | --- | --- |\n");
+    for entry in entries {
+        let (answer, runtime) = match entry.answer {
+            Some(ref answer) => (format!("`{}`", answer), format_runtime(entry.seconds)),
+            None => (entry.error.clone().unwrap_or_default(), "-".to_string()),
+        };
+
+        table.push_str(&format!("| `{}` | {} | {} |\n", entry.command, answer, runtime));
+    }
+
+    let build = if cfg!(debug_assertions) { "debug" } else { "release" };
+    table.push_str(&format!("\nRuntimes were measured with a {} build.\n", build));
+
+    table
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportError {
+    /// The document does not have both of the markers, in order.
+    MissingMarkers,
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReportError::MissingMarkers => write!(
+                f, "Could not find {} followed by {}", START_MARKER, END_MARKER
+            ),
+        }
+    }
+}
+
+impl Error for ReportError {}
+
+/// Replaces everything between the start and end markers in the given
+/// document with the given section, keeping the markers themselves.
+pub fn replace_section(document: &str, section: &str) -> Result<String, ReportError> {
+    let start = document.find(START_MARKER).ok_or(ReportError::MissingMarkers)?
+        + START_MARKER.len();
+    let end = document[start..].find(END_MARKER).ok_or(ReportError::MissingMarkers)?
+        + start;
+
+    Ok(format!("{}\n{}{}", &document[..start], section, &document[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(answer: Option<&str>, error: Option<&str>) -> Entry {
+        Entry {
+            day: 2,
+            part: "two".to_string(),
+            command: "cargo run -- day2 two < inputs/2.txt".to_string(),
+            answer: answer.map(|a| a.to_string()),
+            error: error.map(|e| e.to_string()),
+            seconds: 1.5,
+        }
+    }
+
+    #[test]
+    fn render_it_shows_errors_in_place_of_answers() {
+        let table = render(&[entry(None, Some("Timed out after 5 seconds"))]);
+
+        assert!(table.contains("| Timed out after 5 seconds | - |"));
+    }
+
+    #[test]
+    fn render_it_uses_seconds_for_long_runtimes() {
+        let table = render(&[entry(Some("abc"), None)]);
+
+        assert!(table.contains("| `abc` | 1.50 s |"));
+    }
+
+    #[test]
+    fn replace_section_it_replaces_only_between_markers() {
+        let document = "# Title\n\n<!-- results:start -->\nold\n<!-- results:end -->\n\nEnd\n";
+
+        let actual = replace_section(document, "new\n").unwrap();
+
+        assert_eq!(
+            actual,
+            "# Title\n\n<!-- results:start -->\nnew\n<!-- results:end -->\n\nEnd\n"
+        );
+    }
+
+    #[test]
+    fn replace_section_it_is_idempotent() {
+        let document = "<!-- results:start -->\n<!-- results:end -->\n";
+
+        let once = replace_section(document, "table\n").unwrap();
+        let twice = replace_section(&once, "table\n").unwrap();
+
+        assert_eq!(once, twice);
+    }
+
+    #[test]
+    fn replace_section_it_requires_both_markers() {
+        let actual = replace_section("<!-- results:end -->\n<!-- results:start -->\n", "");
+
+        assert_eq!(actual, Err(ReportError::MissingMarkers));
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use one;
+use three;
+use two;
+
+/// One of the two parts of a day's puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    /// Returns the number of the part, as used by the puzzle website.
+    pub fn number(self) -> u32 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Part, ()> {
+        match s {
+            "one" | "1" => Ok(Part::One),
+            "two" | "2" => Ok(Part::Two),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Part::One => write!(f, "one"),
+            Part::Two => write!(f, "two"),
+        }
+    }
+}
+
+/// A solution to one part of a day's puzzle, taking the puzzle input and
+/// returning the answer, or why there is none, such as a malformed input.
+pub struct Solution {
+    pub day: u32,
+    pub part: Part,
+    pub solve: fn(&str) -> Result<String, String>,
+}
+
+/// All of the solutions that have been implemented, in order.
+pub static SOLUTIONS: [Solution; 6] = [
+    Solution { day: 1, part: Part::One, solve: |input| one::part_one(input).map_err(|e| e.to_string()) },
+    Solution { day: 1, part: Part::Two, solve: |input| one::part_two(input).map_err(|e| e.to_string()) },
+    Solution { day: 2, part: Part::One, solve: |input| Ok(two::part_one(input)) },
+    Solution { day: 2, part: Part::Two, solve: |input| Ok(two::part_two(input)) },
+    Solution { day: 3, part: Part::One, solve: |input| Ok(three::part_one(input)) },
+    Solution { day: 3, part: Part::Two, solve: |input| Ok(three::part_two(input)) },
+];
+
+/// Returns the solution for the given part of the given day, if it has been
+/// implemented.
+pub fn find(day: u32, part: Part) -> Option<&'static Solution> {
+    SOLUTIONS.iter().find(|s| s.day == day && s.part == part)
+}
+
+/// The answer produced by a solution, along with how long it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer<T = String> {
+    pub answer: T,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunError {
+    /// The solution did not finish within the given time.
+    TimedOut(Duration),
+    /// The solution panicked, which is a bug in the solution.
+    Panicked,
+    /// The solution could not answer, for the given reason.
+    Failed(String),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RunError::TimedOut(t) => {
+                write!(f, "Timed out after {} seconds", t.as_secs())
+            }
+            RunError::Panicked => write!(f, "The solution panicked"),
+            RunError::Failed(ref reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// Runs the given solution on the given input, giving up on it if it does not
+/// finish within the timeout.
+///
+/// The solution is run on a separate thread, which is left running if it
+/// times out.
+pub fn run(solution: &Solution, input: String, timeout: Option<Duration>) -> Result<Answer, RunError> {
+    let answer = run_fn(solution.solve, input, timeout)?;
+
+    Ok(Answer {
+        answer: answer.answer.map_err(RunError::Failed)?,
+        elapsed: answer.elapsed,
+    })
+}
+
+/// Runs the given function on the given input in the same way as `run`, for
+/// solving a part in some way other than its default one, or for getting
+/// more than just the answer.
+pub fn run_fn<F, T>(solve: F, input: String, timeout: Option<Duration>) -> Result<Answer<T>, RunError>
+    where F: FnOnce(&str) -> T + Send + 'static, T: Send + 'static {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let start = Instant::now();
+        let answer = solve(&input);
+
+        // The receiver is gone if the run has already timed out
+        let _ = sender.send(Answer { answer, elapsed: start.elapsed() });
+    });
+
+    match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout).map_err(|e| match e {
+            mpsc::RecvTimeoutError::Timeout => RunError::TimedOut(timeout),
+            mpsc::RecvTimeoutError::Disconnected => RunError::Panicked,
+        }),
+        None => receiver.recv().map_err(|_| RunError::Panicked),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slow(_: &str) -> Result<String, String> {
+        thread::sleep(Duration::from_secs(5));
+
+        Ok("done".to_string())
+    }
+
+    fn broken(input: &str) -> Result<String, String> {
+        Ok(input.parse::<i32>().unwrap().to_string())
+    }
+
+    #[test]
+    fn find_it_finds_implemented_parts() {
+        let solution = find(2, Part::Two).unwrap();
+
+        assert_eq!((solution.day, solution.part), (2, Part::Two));
+        assert!(find(25, Part::One).is_none());
+    }
+
+    #[test]
+    fn run_it_returns_the_answer() {
+        let solution = find(1, Part::One).unwrap();
+
+        let actual = run(solution, "+2\n+1\n-4\n+5\n".to_string(), None);
+
+        assert_eq!(actual.unwrap().answer, "4");
+    }
+
+    #[test]
+    fn run_it_times_out() {
+        let solution = Solution { day: 0, part: Part::One, solve: slow };
+        let timeout = Duration::from_millis(10);
+
+        let actual = run(&solution, String::new(), Some(timeout));
+
+        assert_eq!(actual, Err(RunError::TimedOut(timeout)));
+    }
+
+    #[test]
+    fn run_fn_it_runs_closures() {
+        let suffix = "!".to_string();
+
+        let actual = run_fn(move |input| format!("{}{}", input, suffix), "a".to_string(), None);
+
+        assert_eq!(actual.unwrap().answer, "a!");
+    }
+
+    #[test]
+    fn run_it_reports_why_there_is_no_answer() {
+        let solution = find(1, Part::One).unwrap();
+
+        let actual = run(solution, "+1\n+x\n".to_string(), None);
+
+        assert_eq!(actual, Err(RunError::Failed("Invalid change \"+x\" at line 2, column 1".to_string())));
+    }
+
+    #[test]
+    fn run_it_reports_panics() {
+        let solution = Solution { day: 0, part: Part::One, solve: broken };
+
+        let actual = run(&solution, "abc".to_string(), None);
+
+        assert_eq!(actual, Err(RunError::Panicked));
+    }
+}
@@ -0,0 +1,359 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use client::{self, Client, ClientError, YEAR};
+use solutions::Part;
+
+/// The name of the file in the cache directory that submissions are logged to.
+pub const LOG_FILE: &str = "submissions.log";
+
+/// The website's verdict on a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    Incorrect,
+    TooHigh,
+    TooLow,
+    /// An answer was submitted too recently, so this one was not checked.
+    RateLimited,
+    /// The part has already been solved, so the answer was not checked.
+    AlreadySolved,
+    /// The response could not be understood.
+    Unrecognized,
+    /// The request failed or the server responded with an error status, so
+    /// the answer was not checked.
+    Failed,
+}
+
+impl Outcome {
+    /// Returns true if the answer was checked and found to be wrong.
+    pub fn is_wrong(self) -> bool {
+        matches!(self, Outcome::Incorrect | Outcome::TooHigh | Outcome::TooLow)
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Outcome, ()> {
+        match s {
+            "correct" => Ok(Outcome::Correct),
+            "incorrect" => Ok(Outcome::Incorrect),
+            "too-high" => Ok(Outcome::TooHigh),
+            "too-low" => Ok(Outcome::TooLow),
+            "rate-limited" => Ok(Outcome::RateLimited),
+            "already-solved" => Ok(Outcome::AlreadySolved),
+            "unrecognized" => Ok(Outcome::Unrecognized),
+            "failed" => Ok(Outcome::Failed),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Outcome::Correct => "correct",
+            Outcome::Incorrect => "incorrect",
+            Outcome::TooHigh => "too-high",
+            Outcome::TooLow => "too-low",
+            Outcome::RateLimited => "rate-limited",
+            Outcome::AlreadySolved => "already-solved",
+            Outcome::Unrecognized => "unrecognized",
+            Outcome::Failed => "failed",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Works out the outcome of a submission from the page that the website
+/// responded with.
+///
+/// ```
+/// use advent_of_code_2018::submit::{self, Outcome};
+///
+/// let page = "<article><p>That's not the right answer; your answer is too \
+///             high.</p></article>";
+///
+/// assert_eq!(submit::parse_response(page), Outcome::TooHigh);
+/// ```
+pub fn parse_response(page: &str) -> Outcome {
+    if page.contains("That's the right answer") {
+        Outcome::Correct
+    } else if page.contains("That's not the right answer") {
+        if page.contains("too high") {
+            Outcome::TooHigh
+        } else if page.contains("too low") {
+            Outcome::TooLow
+        } else {
+            Outcome::Incorrect
+        }
+    } else if page.contains("You gave an answer too recently") {
+        Outcome::RateLimited
+    } else if page.contains("You don't seem to be solving the right level") {
+        Outcome::AlreadySolved
+    } else {
+        Outcome::Unrecognized
+    }
+}
+
+/// A single attempt at submitting an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Submission {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub day: u32,
+    pub part: Part,
+    pub answer: String,
+    pub outcome: Outcome,
+}
+
+impl Submission {
+    /// Parses a line of the submission log, which has tab separated fields.
+    fn parse(line: &str) -> Option<Submission> {
+        let mut fields = line.splitn(5, '\t');
+
+        Some(Submission {
+            time: fields.next()?.parse().ok()?,
+            day: fields.next()?.parse().ok()?,
+            part: fields.next()?.parse().ok()?,
+            outcome: fields.next()?.parse().ok()?,
+            answer: fields.next()?.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Submission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "{}\t{}\t{}\t{}\t{}",
+            self.time, self.day, self.part.number(), self.outcome, self.answer
+        )
+    }
+}
+
+/// A log of every submitted answer, kept in the cache directory.
+pub struct SubmissionLog {
+    path: PathBuf,
+}
+
+impl SubmissionLog {
+    pub fn new(cache_dir: &Path) -> SubmissionLog {
+        SubmissionLog { path: cache_dir.join(LOG_FILE) }
+    }
+
+    /// Returns all of the logged submissions, oldest first. Lines that cannot
+    /// be parsed are skipped.
+    pub fn read(&self) -> io::Result<Vec<Submission>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(contents.lines().filter_map(Submission::parse).collect())
+    }
+
+    pub fn record(&self, submission: &Submission) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+
+        writeln!(file, "{}", submission)
+    }
+
+    /// Returns the earlier submission of the given answer that was found to
+    /// be wrong, if there is one.
+    pub fn find_wrong(&self, day: u32, part: Part, answer: &str) -> io::Result<Option<Submission>> {
+        let submissions = self.read()?;
+
+        Ok(submissions.into_iter().find(|s| {
+            s.day == day && s.part == part && s.answer == answer && s.outcome.is_wrong()
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    /// The answer was already submitted and found to be wrong.
+    AlreadyWrong(Submission),
+    Client(ClientError),
+    Io(io::Error),
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SubmitError::AlreadyWrong(ref s) => write!(
+                f, "Not submitting {}, it was already found to be {}", s.answer, s.outcome
+            ),
+            SubmitError::Client(ref err) => write!(f, "{}", err),
+            SubmitError::Io(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for SubmitError {}
+
+impl From<ClientError> for SubmitError {
+    fn from(err: ClientError) -> SubmitError {
+        SubmitError::Client(err)
+    }
+}
+
+impl From<io::Error> for SubmitError {
+    fn from(err: io::Error) -> SubmitError {
+        SubmitError::Io(err)
+    }
+}
+
+/// Submits the given answer for the given part and records the attempt in
+/// the log, including attempts where the request itself failed.
+///
+/// Answers that the log shows were already found to be wrong are refused
+/// without creating the client.
+pub fn submit<F>(log: &SubmissionLog, day: u32, part: Part, answer: &str, client: F) -> Result<Outcome, SubmitError>
+    where F: FnOnce() -> Result<Client, ClientError> {
+    if let Some(earlier) = log.find_wrong(day, part, answer)? {
+        return Err(SubmitError::AlreadyWrong(earlier));
+    }
+
+    let level = part.number().to_string();
+    let response = client()?.post_form(
+        &format!("/{}/day/{}/answer", YEAR, day),
+        &[("level", &level), ("answer", answer)],
+    );
+
+    let outcome = match response {
+        Ok(ref page) => parse_response(page),
+        Err(_) => Outcome::Failed,
+    };
+
+    log.record(&Submission {
+        time: client::now().as_secs(),
+        day,
+        part,
+        answer: answer.to_string(),
+        outcome,
+    })?;
+
+    response?;
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use client::stub;
+
+    const CORRECT: &str = "<main><article><p>That's the right answer! You are one \
+                           gold star closer to fixing the time stream.</p></article></main>";
+    const TOO_LOW: &str = "<main><article><p>That's not the right answer; your \
+                           answer is too low.</p></article></main>";
+
+    fn test_client(base_url: &str, cache_dir: &Path) -> Result<Client, ClientError> {
+        Ok(Client::new(base_url, "abc123", Duration::from_secs(0), cache_dir))
+    }
+
+    #[test]
+    fn parse_response_it_recognizes_outcomes() {
+        assert_eq!(parse_response(CORRECT), Outcome::Correct);
+        assert_eq!(parse_response(TOO_LOW), Outcome::TooLow);
+        assert_eq!(parse_response("That's not the right answer."), Outcome::Incorrect);
+        assert_eq!(
+            parse_response("You gave an answer too recently; you have to wait."),
+            Outcome::RateLimited
+        );
+        assert_eq!(
+            parse_response("You don't seem to be solving the right level."),
+            Outcome::AlreadySolved
+        );
+        assert_eq!(parse_response("<html></html>"), Outcome::Unrecognized);
+    }
+
+    #[test]
+    fn submission_log_it_reads_recorded_submissions() {
+        let log = SubmissionLog::new(&stub::temp_dir("log_roundtrip"));
+
+        let submission = Submission {
+            time: 1543651200,
+            day: 1,
+            part: Part::Two,
+            answer: "56360".to_string(),
+            outcome: Outcome::TooHigh,
+        };
+
+        log.record(&submission).unwrap();
+
+        assert_eq!(log.read().unwrap(), vec![submission]);
+    }
+
+    #[test]
+    fn submit_it_posts_and_logs_the_answer() {
+        let (base_url, server) = stub::serve(vec![(200, CORRECT)]);
+        let cache_dir = stub::temp_dir("submit_posts");
+        let log = SubmissionLog::new(&cache_dir);
+
+        let actual = submit(&log, 1, Part::One, "411", || test_client(&base_url, &cache_dir));
+
+        let requests = server.join().unwrap();
+        let logged = log.read().unwrap();
+
+        assert_eq!(actual.unwrap(), Outcome::Correct);
+        assert!(requests[0].starts_with("POST /2018/day/1/answer "));
+        assert!(requests[0].ends_with("level=1&answer=411"));
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].outcome, Outcome::Correct);
+    }
+
+    #[test]
+    fn submit_it_refuses_answers_already_found_wrong() {
+        let (base_url, server) = stub::serve(vec![(200, TOO_LOW)]);
+        let cache_dir = stub::temp_dir("submit_refuses");
+        let log = SubmissionLog::new(&cache_dir);
+
+        let first = submit(&log, 2, Part::Two, "abc", || test_client(&base_url, &cache_dir));
+        server.join().unwrap();
+
+        let second = submit(&log, 2, Part::Two, "abc", || panic!("Should not connect"));
+
+        assert_eq!(first.unwrap(), Outcome::TooLow);
+        match second {
+            Err(SubmitError::AlreadyWrong(ref s)) => assert_eq!(s.outcome, Outcome::TooLow),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert_eq!(log.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn submit_it_logs_failed_requests() {
+        let (base_url, server) = stub::serve(vec![(500, "Internal Server Error")]);
+        let cache_dir = stub::temp_dir("submit_logs_failed");
+        let log = SubmissionLog::new(&cache_dir);
+
+        let actual = submit(&log, 1, Part::Two, "56360", || test_client(&base_url, &cache_dir));
+        server.join().unwrap();
+
+        let logged = log.read().unwrap();
+
+        match actual {
+            Err(SubmitError::Client(ClientError::Status(500, _))) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].answer, "56360");
+        assert_eq!(logged[0].outcome, Outcome::Failed);
+    }
+}
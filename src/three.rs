@@ -1,26 +1,21 @@
-use std::io;
-use std::io::prelude::*;
-
-pub fn part_one() {
-    let claims = read_input();
+pub fn part_one(input: &str) -> String {
+    let claims = read_input(input);
     let output = count_claim_overlap(&claims);
 
-    println!("{}", output);
+    output.to_string()
 }
 
-pub fn part_two() {
-    let claims = read_input();
+pub fn part_two(input: &str) -> String {
+    let claims = read_input(input);
     let output = find_non_overlapping_claim(&claims).unwrap();
 
-    println!("{}", output);
+    output.to_string()
 }
 
-pub fn read_input() -> Vec<Claim> {
-    let stdin = io::stdin();
-
+pub fn read_input(input: &str) -> Vec<Claim> {
     let mut claims: Vec<Claim> = Vec::new();
-    for line in stdin.lock().lines() {
-        let (id, x1, y1, width, height) = extract_claim_info(&line.unwrap());
+    for line in input.lines() {
+        let (id, x1, y1, width, height) = extract_claim_info(line);
 
         let claim = construct_claim(id, x1, y1, width, height);
 
@@ -1,32 +1,27 @@
-extern crate multimap;
-
 use std::collections::LinkedList;
-use std::collections::HashMap;
-use std::io;
-use std::io::prelude::*;
-
-use self::multimap::MultiMap;
-
-pub fn part_one() {
-    let input = read_input();
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+pub fn part_one(input: &str) -> String {
+    let input = read_input(input);
     let output = checksum_ids(&input);
 
-    println!("{}", output);
+    output.to_string()
 }
 
-pub fn part_two() {
-    let input = read_input();
-    let output = find_common_chars_in_1_diff(&input).unwrap();
+pub fn part_two(input: &str) -> String {
+    let input = read_input(input);
 
-    println!("{}", output);
+    find_common_chars_in_1_diff(&input).unwrap()
 }
 
-pub fn read_input() -> LinkedList<String> {
-    let stdin = io::stdin();
-
+pub fn read_input(input: &str) -> LinkedList<String> {
     let mut box_ids: LinkedList<String> = LinkedList::new();
-    for line in stdin.lock().lines() {
-        let id = line.unwrap();
+    for line in input.lines() {
+        let id = line.to_string();
 
         // Ignore the last line, since it is empty
         if id != "" {
@@ -81,23 +76,19 @@ pub fn calc_letter_counts(id: &str) -> HashMap<char, i32> {
 }
 
 pub fn find_common_chars_in_1_diff(box_ids: &LinkedList<String>) -> Option<String> {
-    let mut hashes: MultiMap<i32, &str> = MultiMap::new();
+    // Two IDs only share a key if they match everywhere except at the
+    // position of the removed character
+    let mut first_ids: HashMap<(usize, String), &str> = HashMap::new();
 
     for id in box_ids.iter() {
-        for char_i_to_remove in 0..id.len() {
+        for char_i_to_remove in 0..id.chars().count() {
             let remaining_chars = remove_char_at(id, char_i_to_remove);
-            let hash = hash_string(&remaining_chars);
 
-            hashes.insert(hash, id);
+            let first_id = *first_ids.entry((char_i_to_remove, remaining_chars)).or_insert(id);
 
-            let possible_matches = hashes.get_vec(&hash);
-
-            if let Some(possible_matches) = possible_matches {
-                for possible_match in possible_matches {
-                    if off_by_one(id, possible_match) {
-                        return Some(common_chars(id, possible_match))
-                    }
-                }
+            // Only a duplicate of the ID matches at every position
+            if first_id != id {
+                return Some(common_chars(id, first_id))
             }
         }
     }
@@ -106,24 +97,30 @@ pub fn find_common_chars_in_1_diff(box_ids: &LinkedList<String>) -> Option<Strin
 }
 
 pub fn off_by_one(str_a: &str, str_b: &str) -> bool {
+    let mut chars_a = str_a.chars();
+    let mut chars_b = str_b.chars();
     let mut diff = 0;
 
-    for (i, c) in str_a.chars().enumerate() {
-        let c2 = str_b.as_bytes()[i] as char;   // Assume ascii string
-
-        if c != c2 {
-            if diff < 1 {
-                diff += 1;
-            } else {
-                return false
+    loop {
+        match (chars_a.next(), chars_b.next()) {
+            (Some(c), Some(c2)) => {
+                if c != c2 {
+                    if diff < 1 {
+                        diff += 1;
+                    } else {
+                        return false
+                    }
+                }
             }
+            (None, None) => return diff == 1,
+            // Strings with different numbers of characters are never off by one
+            _ => return false,
         }
     }
-
-    diff == 1
 }
 
 pub fn common_chars(str_1: &str, str_b: &str) -> String {
+    // Any characters past the end of the shorter string are not in common
     str_1.chars().zip(str_b.chars())
         .filter(|(a, b)| a == b)
         .map(|(c, _)| c)
@@ -131,6 +128,8 @@ pub fn common_chars(str_1: &str, str_b: &str) -> String {
 }
 
 pub fn remove_char_at(id: &str, i: usize) -> String {
+    // The index counts characters rather than bytes, and if it is past the
+    // end then nothing is removed
     let mut chars_to_keep = Vec::new();
 
     for (j, c) in id.chars().enumerate() {
@@ -154,11 +153,454 @@ pub fn hash_string(string: &str) -> i32 {
     hash
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// The number of positions at which two IDs of the same length differ.
+    Hamming,
+    /// The fewest insertions, deletions and substitutions of single
+    /// characters that turn one ID into the other.
+    Levenshtein,
+}
+
+impl FromStr for Metric {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Metric, ()> {
+        match s {
+            "hamming" => Ok(Metric::Hamming),
+            "levenshtein" => Ok(Metric::Levenshtein),
+            _ => Err(()),
+        }
+    }
+}
+
+/// One step in lining up two IDs against each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diff {
+    Same(char),
+    Changed(char, char),
+    /// A character that is only in the second ID.
+    Inserted(char),
+    /// A character that is only in the first ID.
+    Deleted(char),
+}
+
+/// Two IDs within some distance of each other, along with how they line
+/// up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pair<'a> {
+    pub first: &'a str,
+    pub second: &'a str,
+    pub distance: usize,
+    pub diff: Vec<Diff>,
+}
+
+impl<'a> fmt::Display for Pair<'a> {
+    /// Writes the two IDs lined up on separate lines, with gaps shown as `-`,
+    /// followed by a line marking where they differ.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = String::new();
+        let mut second = String::new();
+        let mut marks = String::new();
+
+        for step in &self.diff {
+            let (a, b) = match *step {
+                Diff::Same(c) => (c, c),
+                Diff::Changed(a, b) => (a, b),
+                Diff::Inserted(b) => ('-', b),
+                Diff::Deleted(a) => (a, '-'),
+            };
+
+            first.push(a);
+            second.push(b);
+            marks.push(if let Diff::Same(_) = *step { ' ' } else { '^' });
+        }
+
+        writeln!(f, "{}", first)?;
+        writeln!(f, "{}", second)?;
+        writeln!(f, "{}", marks.trim_end())
+    }
+}
+
+/// Finds every pair of IDs within the given distance of each other, closest
+/// first, and then in the order of the IDs.
+///
+/// Most pairs are ruled out without working out their distance. IDs whose
+/// lengths differ by more than the distance are too far apart. And if an ID
+/// is cut into `k + 1` pieces, then `k` edits leave at least one of them
+/// untouched. So any ID within distance `k` contains one of the pieces,
+/// shifted by at most `k` characters, or not at all for the Hamming
+/// distance. An index from each piece to the IDs it was cut from gives the
+/// few IDs worth comparing. Once `k` reaches the length of the longest ID
+/// there are no pieces to cut, and every pair is compared instead.
+///
+/// ```
+/// use std::collections::LinkedList;
+///
+/// use advent_of_code_2018::two::{self, Metric};
+///
+/// let box_ids: LinkedList<String> = vec!["abcdef", "abdef", "abxdef", "uvwxyz"]
+///     .into_iter().map(String::from).collect();
+///
+/// let pairs = two::find_pairs_within(&box_ids, 1, Metric::Levenshtein);
+/// let found: Vec<_> = pairs.iter().map(|p| (p.first, p.second, p.distance)).collect();
+///
+/// assert_eq!(found, vec![("abcdef", "abdef", 1), ("abcdef", "abxdef", 1), ("abdef", "abxdef", 1)]);
+/// assert_eq!(pairs[0].to_string(), "abcdef\nab-def\n  ^\n");
+/// ```
+pub fn find_pairs_within(box_ids: &LinkedList<String>, k: usize, metric: Metric) -> Vec<Pair<'_>> {
+    let ids: Vec<(&str, Vec<char>)> = box_ids.iter()
+        .map(|id| (id.as_str(), id.chars().collect()))
+        .collect();
+    let longest = ids.iter().map(|(_, chars)| chars.len()).max().unwrap_or(0);
+    let every_pair = k >= longest;
+    let max_shift = match metric {
+        Metric::Hamming => 0,
+        Metric::Levenshtein => isize::try_from(k).unwrap_or(isize::MAX),
+    };
+
+    // IDs too short to cut into pieces are kept by length instead
+    let mut short_by_length: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut by_piece: HashMap<(usize, usize, &[char]), Vec<usize>> = HashMap::new();
+    let mut compared = vec![usize::MAX; ids.len()];
+    let mut pairs = Vec::new();
+
+    for (i, &(id, ref chars)) in ids.iter().enumerate() {
+        let mut candidates: Vec<usize> = Vec::new();
+        if every_pair {
+            candidates.extend(0..i);
+        } else {
+            let lengths = chars.len().saturating_sub(max_shift as usize)..=chars.len() + max_shift as usize;
+            for len in lengths {
+                if len <= k {
+                    candidates.extend(short_by_length.get(&len).into_iter().flatten());
+                    continue;
+                }
+
+                for piece in 0..=k {
+                    let (start, end) = piece_bounds(len, k, piece);
+
+                    for shift in -max_shift..=max_shift {
+                        let start = start as isize + shift;
+                        let end = end as isize + shift;
+                        if start < 0 || end as usize > chars.len() {
+                            continue;
+                        }
+
+                        let key = (len, piece, &chars[start as usize..end as usize]);
+                        candidates.extend(by_piece.get(&key).into_iter().flatten());
+                    }
+                }
+            }
+        }
+
+        for j in candidates {
+            // Each pair only needs to be compared once
+            if compared[j] == i {
+                continue;
+            }
+            compared[j] = i;
+
+            let (other, ref other_chars) = ids[j];
+            if let Some(distance) = distance_within(other_chars, chars, k, metric) {
+                pairs.push((j, i, Pair {
+                    first: other,
+                    second: id,
+                    distance,
+                    diff: align(other_chars, chars, metric),
+                }));
+            }
+        }
+
+        if chars.len() <= k {
+            short_by_length.entry(chars.len()).or_default().push(i);
+        } else {
+            for piece in 0..=k {
+                let (start, end) = piece_bounds(chars.len(), k, piece);
+
+                by_piece.entry((chars.len(), piece, &chars[start..end])).or_default().push(i);
+            }
+        }
+    }
+
+    pairs.sort_by_key(|&(j, i, ref pair)| (pair.distance, j, i));
+
+    pairs.into_iter().map(|(_, _, pair)| pair).collect()
+}
+
+/// Returns where the given piece starts and ends when an ID of the given
+/// length is cut into `k + 1` pieces of about the same length.
+fn piece_bounds(len: usize, k: usize, piece: usize) -> (usize, usize) {
+    (piece * len / (k + 1), (piece + 1) * len / (k + 1))
+}
+
+/// Returns the distance between the two IDs under the given metric, or
+/// `None` if it is not defined, which is only the case for the Hamming
+/// distance between IDs of different lengths.
+///
+/// ```
+/// use advent_of_code_2018::two::{self, Metric};
+///
+/// assert_eq!(two::distance("kitten", "sitting", Metric::Levenshtein), Some(3));
+/// assert_eq!(two::distance("kitten", "sitten", Metric::Hamming), Some(1));
+/// assert_eq!(two::distance("kitten", "sitting", Metric::Hamming), None);
+/// ```
+pub fn distance(str_a: &str, str_b: &str, metric: Metric) -> Option<usize> {
+    let a: Vec<char> = str_a.chars().collect();
+    let b: Vec<char> = str_b.chars().collect();
+
+    distance_within(&a, &b, usize::MAX, metric)
+}
+
+/// Returns the distance between the two IDs if it is at most the given
+/// limit, giving up as soon as it is sure to be further.
+fn distance_within(a: &[char], b: &[char], limit: usize, metric: Metric) -> Option<usize> {
+    if metric == Metric::Hamming {
+        if a.len() != b.len() {
+            return None;
+        }
+
+        let distance = a.iter().zip(b).filter(|&(x, y)| x != y).count();
+
+        return if distance <= limit { Some(distance) } else { None };
+    }
+
+    // One row of the table of distances between prefixes at a time
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, &x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, &y) in b.iter().enumerate() {
+            let substitute = diagonal + if x == y { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+
+        // Distances never shrink further down the table
+        if row.iter().all(|&distance| distance > limit) {
+            return None;
+        }
+    }
+
+    Some(row[b.len()]).filter(|&distance| distance <= limit)
+}
+
+/// Lines up the two IDs with the fewest differences under the given metric.
+/// The IDs must have the same length for the Hamming distance.
+fn align(a: &[char], b: &[char], metric: Metric) -> Vec<Diff> {
+    let same_or_changed = |x: char, y: char| if x == y { Diff::Same(x) } else { Diff::Changed(x, y) };
+
+    if metric == Metric::Hamming {
+        return a.iter().zip(b).map(|(&x, &y)| same_or_changed(x, y)).collect();
+    }
+
+    let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in table[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitute = table[i - 1][j - 1] + if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            table[i][j] = substitute.min(table[i - 1][j] + 1).min(table[i][j - 1] + 1);
+        }
+    }
+
+    // Walk back from the end, preferring to line characters up
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && table[i][j] == table[i - 1][j - 1] + if a[i - 1] == b[j - 1] { 0 } else { 1 } {
+            diff.push(same_or_changed(a[i - 1], b[j - 1]));
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && table[i][j] == table[i - 1][j] + 1 {
+            diff.push(Diff::Deleted(a[i - 1]));
+            i -= 1;
+        } else {
+            diff.push(Diff::Inserted(b[j - 1]));
+            j -= 1;
+        }
+    }
+    diff.reverse();
+
+    diff
+}
+
+/// A BK-tree of box IDs, for finding the IDs closest to a given one without
+/// measuring the distance to all of them.
+///
+/// Each ID is stored under the first ID it was compared to, keyed by the
+/// distance between them. By the triangle inequality, only the IDs whose key
+/// is within the search radius of the distance to their parent can be
+/// within the radius themselves.
+///
+/// The Hamming distance is only defined between IDs of the same length, so
+/// with it there is a separate tree for each length, and searches only look
+/// at IDs of the same length.
+///
+/// ```
+/// use advent_of_code_2018::two::{BkTree, Metric};
+///
+/// let mut tree = BkTree::new(Metric::Levenshtein);
+/// for id in ["abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz"].iter() {
+///     tree.insert(id);
+/// }
+///
+/// assert_eq!(tree.within("fgxij", 1), vec![(1, "fghij"), (1, "fguij")]);
+/// assert_eq!(tree.nearest("abcd", 2), vec![(1, "abcde"), (3, "axcye")]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BkTree {
+    metric: Metric,
+    roots: HashMap<usize, BkNode>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct BkNode {
+    id: String,
+    chars: Vec<char>,
+    children: HashMap<usize, BkNode>,
+}
+
+impl BkNode {
+    fn distance(&self, chars: &[char], metric: Metric) -> usize {
+        distance_within(&self.chars, chars, usize::MAX, metric)
+            .expect("IDs of different lengths are kept in different trees")
+    }
+}
+
+impl BkTree {
+    pub fn new(metric: Metric) -> BkTree {
+        BkTree { metric, roots: HashMap::new(), len: 0 }
+    }
+
+    /// Returns how many different IDs are in the tree.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns which tree IDs of the given length go in.
+    fn root_key(&self, len: usize) -> usize {
+        match self.metric {
+            Metric::Hamming => len,
+            Metric::Levenshtein => 0,
+        }
+    }
+
+    /// Adds the given ID to the tree, returning false if it was already
+    /// there.
+    pub fn insert(&mut self, id: &str) -> bool {
+        let chars: Vec<char> = id.chars().collect();
+        let metric = self.metric;
+
+        let mut node = match self.roots.entry(self.root_key(chars.len())) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(BkNode { id: id.to_string(), chars, children: HashMap::new() });
+                self.len += 1;
+
+                return true;
+            }
+        };
+
+        loop {
+            let distance = node.distance(&chars, metric);
+            if distance == 0 {
+                return false;
+            }
+
+            node = match node.children.entry(distance) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    entry.insert(BkNode { id: id.to_string(), chars, children: HashMap::new() });
+                    self.len += 1;
+
+                    return true;
+                }
+            };
+        }
+    }
+
+    /// Returns the IDs within the given distance of the given one, along
+    /// with their distances, closest first and then in alphabetical order.
+    pub fn within(&self, id: &str, radius: usize) -> Vec<(usize, &str)> {
+        let chars: Vec<char> = id.chars().collect();
+        let mut found = Vec::new();
+
+        let mut unvisited: Vec<&BkNode> = self.roots.get(&self.root_key(chars.len())).into_iter().collect();
+        while let Some(node) = unvisited.pop() {
+            let distance = node.distance(&chars, self.metric);
+            if distance <= radius {
+                found.push((distance, node.id.as_str()));
+            }
+
+            unvisited.extend(children_within(node, distance, radius));
+        }
+
+        found.sort();
+
+        found
+    }
+
+    /// Returns the given number of IDs closest to the given one, along with
+    /// their distances, closest first and then in alphabetical order.
+    pub fn nearest(&self, id: &str, count: usize) -> Vec<(usize, &str)> {
+        let chars: Vec<char> = id.chars().collect();
+        if count == 0 {
+            return Vec::new();
+        }
+
+        // The furthest of the closest IDs found so far is on top
+        let mut closest = BinaryHeap::new();
+
+        let mut unvisited: Vec<&BkNode> = self.roots.get(&self.root_key(chars.len())).into_iter().collect();
+        while let Some(node) = unvisited.pop() {
+            let found = (node.distance(&chars, self.metric), node.id.as_str());
+            if closest.len() < count || Some(&found) < closest.peek() {
+                closest.push(found);
+                if closest.len() > count {
+                    closest.pop();
+                }
+            }
+
+            // Ties with the furthest one could still come first alphabetically
+            let radius = match closest.peek() {
+                Some(&(furthest, _)) if closest.len() == count => furthest,
+                _ => usize::MAX,
+            };
+            unvisited.extend(children_within(node, found.0, radius));
+        }
+
+        closest.into_sorted_vec()
+    }
+}
+
+/// Returns the children of the node that could be within the radius of an
+/// ID at the given distance from the node.
+fn children_within(node: &BkNode, distance: usize, radius: usize) -> impl Iterator<Item = &BkNode> {
+    node.children.iter()
+        .filter(move |&(&key, _)| key.saturating_add(radius) >= distance && key <= distance.saturating_add(radius))
+        .map(|(_, child)| child)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use std::collections::LinkedList;
+    use std::ops::RangeInclusive;
+
+    use one::Generator;
 
     #[test]
     fn calc_letter_counts_it_works_on_empty_string() {
@@ -289,6 +731,46 @@ mod tests {
         assert_eq!(actual, Some("aaba".to_string()));
     }
 
+    #[test]
+    fn find_common_chars_in_1_diff_it_ignores_anagrams() {
+        let mut box_ids = LinkedList::new();
+
+        box_ids.push_back("abcde".to_string());
+        box_ids.push_back("edcba".to_string());
+        box_ids.push_back("abdce".to_string());
+        box_ids.push_back("bacde".to_string());
+
+        let actual = find_common_chars_in_1_diff(&box_ids);
+
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn find_common_chars_in_1_diff_it_skips_duplicates() {
+        let mut box_ids = LinkedList::new();
+
+        box_ids.push_back("abcde".to_string());
+        box_ids.push_back("abcde".to_string());
+        box_ids.push_back("abxde".to_string());
+
+        let actual = find_common_chars_in_1_diff(&box_ids);
+
+        assert_eq!(actual, Some("abde".to_string()));
+    }
+
+    #[test]
+    fn find_common_chars_in_1_diff_it_works_with_non_ascii_strings() {
+        let mut box_ids = LinkedList::new();
+
+        box_ids.push_back("ab".to_string());
+        box_ids.push_back("añb".to_string());
+        box_ids.push_back("aéb".to_string());
+
+        let actual = find_common_chars_in_1_diff(&box_ids);
+
+        assert_eq!(actual, Some("ab".to_string()));
+    }
+
     #[test]
     fn off_by_one_it_works_with_empty_strings() {
         let str_a = "";
@@ -329,6 +811,20 @@ mod tests {
         assert_eq!(actual, false);
     }
 
+    #[test]
+    fn off_by_one_it_works_with_different_length_strings() {
+        assert!(!off_by_one("aba", "ab"));
+        assert!(!off_by_one("ab", "abc"));
+        assert!(!off_by_one("", "a"));
+    }
+
+    #[test]
+    fn off_by_one_it_works_with_non_ascii_strings() {
+        assert!(off_by_one("héllo", "hallo"));
+        assert!(off_by_one("日本語", "日本人"));
+        assert!(!off_by_one("hé", "éh"));
+    }
+
     #[test]
     fn common_chars_it_works_for_empty_strings() {
         let str_a = "";
@@ -359,6 +855,20 @@ mod tests {
         assert_eq!(actual, "ababbaba");
     }
 
+    #[test]
+    fn common_chars_it_works_for_different_length_strings() {
+        let actual = common_chars("abcd", "abx");
+
+        assert_eq!(actual, "ab");
+    }
+
+    #[test]
+    fn common_chars_it_works_for_non_ascii_strings() {
+        let actual = common_chars("naïve", "naïvé");
+
+        assert_eq!(actual, "naïv");
+    }
+
     #[test]
     fn remove_char_at_it_works_on_first_letter() {
         let string = "abcd";
@@ -389,6 +899,155 @@ mod tests {
         assert_eq!(new_string, "abc".to_string());
     }
 
+    #[test]
+    fn remove_char_at_it_works_on_non_ascii_letters() {
+        let string = "héllo";
+        let i = 2;
+
+        let new_string = remove_char_at(string, i);
+
+        assert_eq!(new_string, "hélo".to_string());
+    }
+
+    #[test]
+    fn remove_char_at_it_works_past_the_end() {
+        let string = "abcd";
+        let i = 4;
+
+        let new_string = remove_char_at(string, i);
+
+        assert_eq!(new_string, "abcd".to_string());
+    }
+
+    fn id_list(ids: &[&str]) -> LinkedList<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    /// Returns the given number of random IDs, each with a length in the
+    /// given range and made up of the characters of the alphabet.
+    fn random_ids(seed: u64, count: usize, lengths: RangeInclusive<usize>, alphabet: &str) -> Vec<String> {
+        let mut generator = Generator::new(seed);
+        let alphabet: Vec<char> = alphabet.chars().collect();
+
+        (0..count)
+            .map(|_| {
+                let len = generator.between(*lengths.start() as i128, *lengths.end() as i128);
+
+                (0..len).map(|_| generator.choose(&alphabet)).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_pairs_within_it_matches_comparing_every_pair() {
+        // Short IDs over a small alphabet, so that many pairs are close
+        let ids = random_ids(12345, 150, 3..=8, "abc");
+        let box_ids: LinkedList<String> = ids.iter().cloned().collect();
+
+        for &metric in [Metric::Hamming, Metric::Levenshtein].iter() {
+            for k in 0..4 {
+                let mut expected = Vec::new();
+                for (j, first) in ids.iter().enumerate() {
+                    for second in &ids[j + 1..] {
+                        match distance(first, second, metric) {
+                            Some(d) if d <= k => expected.push((first.as_str(), second.as_str(), d)),
+                            _ => {}
+                        }
+                    }
+                }
+                expected.sort_by_key(|&(_, _, d)| d);
+
+                let actual: Vec<_> = find_pairs_within(&box_ids, k, metric)
+                    .into_iter()
+                    .map(|pair| (pair.first, pair.second, pair.distance))
+                    .collect();
+
+                assert_eq!(actual, expected, "{:?} within {}", metric, k);
+            }
+        }
+    }
+
+    #[test]
+    fn find_pairs_within_it_only_compares_equal_lengths_for_hamming() {
+        let box_ids = id_list(&["abcd", "abc", "abxd"]);
+
+        let pairs = find_pairs_within(&box_ids, 2, Metric::Hamming);
+
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].first, pairs[0].second, pairs[0].distance), ("abcd", "abxd", 1));
+        assert_eq!(pairs[0].to_string(), "abcd\nabxd\n  ^\n");
+    }
+
+    #[test]
+    fn find_pairs_within_it_compares_every_pair_for_large_distances() {
+        let box_ids = id_list(&["abcd", "abc", "xyz"]);
+
+        for &k in &[4, 1_000_000_000, usize::MAX] {
+            let found: Vec<_> = find_pairs_within(&box_ids, k, Metric::Levenshtein)
+                .into_iter()
+                .map(|pair| (pair.first, pair.second, pair.distance))
+                .collect();
+
+            assert_eq!(found, vec![("abcd", "abc", 1), ("abc", "xyz", 3), ("abcd", "xyz", 4)]);
+        }
+
+        assert_eq!(find_pairs_within(&box_ids, usize::MAX, Metric::Hamming).len(), 1);
+    }
+
+    #[test]
+    fn find_pairs_within_it_lines_up_insertions() {
+        let box_ids = id_list(&["kitten", "sitting"]);
+
+        let pairs = find_pairs_within(&box_ids, 3, Metric::Levenshtein);
+
+        assert_eq!(pairs[0].distance, 3);
+        assert_eq!(pairs[0].diff[6], Diff::Inserted('g'));
+        assert_eq!(pairs[0].to_string(), "kitten-\nsitting\n^   ^ ^\n");
+    }
+
+    #[test]
+    fn bk_tree_it_matches_comparing_every_id() {
+        let ids = random_ids(777, 200, 4..=7, "abcd");
+
+        for &metric in [Metric::Hamming, Metric::Levenshtein].iter() {
+            let mut tree = BkTree::new(metric);
+            for id in &ids {
+                tree.insert(id);
+            }
+
+            for query in ["abcd", "aaaaaa", "dcbadcb", "bb"].iter() {
+                let mut all: Vec<(usize, &str)> = ids.iter()
+                    .filter_map(|id| distance(id, query, metric).map(|d| (d, id.as_str())))
+                    .collect();
+                all.sort();
+                all.dedup();
+
+                for radius in 0..4 {
+                    let expected: Vec<_> = all.iter().cloned().filter(|&(d, _)| d <= radius).collect();
+
+                    assert_eq!(tree.within(query, radius), expected, "{:?} within {} of {}", metric, radius, query);
+                }
+                for count in 0..6 {
+                    let expected: Vec<_> = all.iter().cloned().take(count).collect();
+
+                    assert_eq!(tree.nearest(query, count), expected, "{:?} nearest {} to {}", metric, count, query);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bk_tree_it_skips_duplicates() {
+        let mut tree = BkTree::new(Metric::Hamming);
+
+        assert!(tree.insert("abcd"));
+        assert!(tree.insert("abce"));
+        assert!(!tree.insert("abcd"));
+        assert!(tree.insert("abc"));
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.nearest("abcf", 5), vec![(1, "abcd"), (1, "abce")]);
+    }
+
     #[test]
     fn hash_string_it_works_on_empty_string() {
         let string = "";